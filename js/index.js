@@ -9,8 +9,10 @@ rust
       rainFg: 'img/weather/texture-rain-fg.png',
       rainBg: 'img/weather/texture-rain-bg.png',
 
-      stormFg: 'img/weather/texture-storm-lightning-fg.png',
-      stormBg: 'img/weather/texture-storm-lightning-bg.png',
+      stormFg: 'img/weather/texture-rain-fg.png',
+      stormBg: 'img/weather/texture-rain-bg.png',
+      stormFlashFg: 'img/weather/texture-storm-lightning-fg.png',
+      stormFlashBg: 'img/weather/texture-storm-lightning-bg.png',
 
       falloutFg: 'img/weather/texture-fallout-fg.png',
       falloutBg: 'img/weather/texture-fallout-bg.png',
@@ -23,6 +25,8 @@ rust
     }
     new m.RainEffect("container", map).then((effect) => {
       console.log(effect)
+      effect.set_parallax_source("pointer", 1.0);
+      effect.warm_up(20);
       effect.draw();
     });
 
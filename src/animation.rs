@@ -0,0 +1,88 @@
+use crate::{cancel_animation_frame, request_animation_frame};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+/// requestAnimationFrame 循环，可以暂停、恢复和销毁
+///
+/// 循环持有的闭包在 `destroy` 或析构时释放。
+///
+/// Example:
+/// ```rust
+/// let animation = AnimationLoop::new(move || {
+///     rain_drops.borrow_mut().draw();
+/// });
+/// animation.start();
+/// animation.stop();
+/// ```
+pub struct AnimationLoop {
+    callback: FrameCallback,
+    handle: Rc<Cell<Option<i32>>>,
+    running: Rc<Cell<bool>>,
+}
+
+impl AnimationLoop {
+    pub fn new(mut tick: impl FnMut() + 'static) -> Self {
+        let callback: FrameCallback = Rc::new(RefCell::new(None));
+        let handle = Rc::new(Cell::new(None));
+        let running = Rc::new(Cell::new(false));
+
+        let f = callback.clone();
+        let h = handle.clone();
+        let r = running.clone();
+        *callback.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+            h.set(None);
+            if !r.get() {
+                return;
+            }
+            tick();
+            // Schedule ourself for another requestAnimationFrame callback.
+            if r.get() {
+                if let Some(f) = f.borrow().as_ref() {
+                    h.set(Some(request_animation_frame(f)));
+                }
+            }
+        }) as Box<dyn FnMut()>));
+
+        AnimationLoop {
+            callback,
+            handle,
+            running,
+        }
+    }
+
+    pub fn start(&self) {
+        if self.running.get() {
+            return;
+        }
+        if let Some(f) = self.callback.borrow().as_ref() {
+            self.running.set(true);
+            self.handle.set(Some(request_animation_frame(f)));
+        }
+    }
+
+    pub fn stop(&self) {
+        self.running.set(false);
+        if let Some(handle) = self.handle.take() {
+            cancel_animation_frame(handle);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.get()
+    }
+
+    /// 停止循环并释放闭包
+    pub fn destroy(&self) {
+        self.stop();
+        let _ = self.callback.borrow_mut().take();
+    }
+}
+
+impl Drop for AnimationLoop {
+    fn drop(&mut self) {
+        self.destroy();
+    }
+}
@@ -0,0 +1,398 @@
+//! 在命令行中渲染雨滴效果，输出 PNG 静帧、序列帧、GIF / APNG 动画，或直接画到终端
+//!
+//! Example:
+//! ```sh
+//! cargo run --release --features cli --bin rain-render -- \
+//!     --weather storm --width 1200 --height 630 --seed 7 --output storm.png
+//! cargo run --release --features cli --bin rain-render -- \
+//!     --weather rain --frames 120 --output frames
+//! cargo run --release --features cli --bin rain-render -- \
+//!     --width 480 --height 270 --frames 90 --fps 30 --loop-fade 15 --output rain.gif
+//! cargo run --release --features cli --bin rain-render -- \
+//!     --format ansi --frames 600 --fps 30
+//! ```
+
+use rain_effect::export::{seamless_loop, write_apng, write_gif, AnimationOptions};
+use rain_effect::rgba::RgbaImage;
+use rain_effect::software_effect::{SoftwareEffect, SoftwareEffectOptions, SoftwareImages};
+use rain_effect::terminal::TerminalMode;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+Usage: rain-render [options]
+
+Options:
+  --weather <name>      rain, fallout, storm, sun or drizzle [default: rain]
+  --fg <path>           foreground image [default: static/img/weather/texture-<weather>-fg.png]
+  --bg <path>           background image [default: static/img/weather/texture-<weather>-bg.png]
+  --drop-alpha <path>   drop alpha image [default: static/img/drop-alpha.png]
+  --drop-color <path>   drop color image [default: static/img/drop-color.png]
+  --width <px>          output width [default: 1280]
+  --height <px>         output height [default: 720]
+  --dpi <ratio>         pixel density, drops are scaled by it [default: 1]
+  --seed <n>            random seed [default: random]
+  --warm-up <seconds>   simulated time before the first frame [default: 20]
+  --frames <n>          number of frames, 1 writes a single still [default: 1]
+  --fps <n>             frame rate of the sequence [default: 60]
+  --format <format>     png, gif, apng, or ansi / ascii to draw to the terminal
+                        [default: from the output extension]
+  --columns <n>         terminal width in characters [default: $COLUMNS or 80]
+  --rows <n>            terminal height in characters [default: $LINES - 1 or 23]
+  --loop-fade <n>       cross-fade the last n frames into the start for a seamless loop
+                        [default: 0]
+  --colors <n>          GIF palette size, 2 to 256 [default: 256]
+  --no-dither           disable GIF dithering
+  --output <path>       PNG file for a still, directory for a PNG sequence,
+                        file for an animation [default: rain.png or frames]
+  -h, --help            print this help
+";
+
+struct Args {
+    weather: String,
+    fg: Option<PathBuf>,
+    bg: Option<PathBuf>,
+    drop_alpha: PathBuf,
+    drop_color: PathBuf,
+    width: u32,
+    height: u32,
+    dpi: f64,
+    seed: Option<u64>,
+    warm_up: f64,
+    frames: u32,
+    fps: f64,
+    format: Option<String>,
+    columns: u32,
+    rows: u32,
+    loop_fade: u32,
+    colors: usize,
+    dither: bool,
+    output: Option<PathBuf>,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Args {
+            weather: "rain".to_owned(),
+            fg: None,
+            bg: None,
+            drop_alpha: PathBuf::from("static/img/drop-alpha.png"),
+            drop_color: PathBuf::from("static/img/drop-color.png"),
+            width: 1280,
+            height: 720,
+            dpi: 1.0,
+            seed: None,
+            warm_up: 20.0,
+            frames: 1,
+            fps: 60.0,
+            format: None,
+            columns: env_size("COLUMNS").unwrap_or(80),
+            // 留一行给提示符，避免画面滚动
+            rows: env_size("LINES").map_or(23, |lines| lines.saturating_sub(1).max(1)),
+            loop_fade: 0,
+            colors: 256,
+            dither: true,
+            output: None,
+        }
+    }
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        if arg == "-h" || arg == "--help" {
+            print!("{}", USAGE);
+            process::exit(0);
+        }
+        if arg == "--no-dither" {
+            args.dither = false;
+            continue;
+        }
+        let value = iter
+            .next()
+            .ok_or_else(|| format!("missing value for {}", arg))?;
+        let number = |value: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|_| format!("invalid value for {}: {}", arg, value))
+        };
+        match arg.as_str() {
+            "--weather" => args.weather = value,
+            "--fg" => args.fg = Some(PathBuf::from(value)),
+            "--bg" => args.bg = Some(PathBuf::from(value)),
+            "--drop-alpha" => args.drop_alpha = PathBuf::from(value),
+            "--drop-color" => args.drop_color = PathBuf::from(value),
+            "--width" => args.width = number(&value)? as u32,
+            "--height" => args.height = number(&value)? as u32,
+            "--dpi" => args.dpi = number(&value)?,
+            "--seed" => {
+                let seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid value for {}: {}", arg, value))?;
+                args.seed = Some(seed);
+            }
+            "--warm-up" => args.warm_up = number(&value)?,
+            "--frames" => args.frames = number(&value)? as u32,
+            "--fps" => args.fps = number(&value)?,
+            "--format" => args.format = Some(value),
+            "--columns" => args.columns = number(&value)? as u32,
+            "--rows" => args.rows = number(&value)? as u32,
+            "--loop-fade" => args.loop_fade = number(&value)? as u32,
+            "--colors" => args.colors = number(&value)? as usize,
+            "--output" => args.output = Some(PathBuf::from(value)),
+            _ => return Err(format!("unknown option: {}", arg)),
+        }
+    }
+
+    if args.width == 0 || args.height == 0 {
+        return Err("width and height must be positive".to_owned());
+    }
+    if args.columns == 0 || args.rows == 0 {
+        return Err("columns and rows must be positive".to_owned());
+    }
+    if args.frames == 0 {
+        return Err("frames must be positive".to_owned());
+    }
+    if args.fps <= 0.0 || args.dpi <= 0.0 {
+        return Err("fps and dpi must be positive".to_owned());
+    }
+    if !(2..=256).contains(&args.colors) {
+        return Err("colors must be between 2 and 256".to_owned());
+    }
+    if args.loop_fade >= args.frames && args.loop_fade > 0 {
+        return Err("loop-fade must be smaller than frames".to_owned());
+    }
+    Ok(args)
+}
+
+/// 终端大小的环境变量
+fn env_size(name: &str) -> Option<u32> {
+    std::env::var(name).ok()?.parse().ok()
+}
+
+/// 天气图片的默认路径
+fn weather_image(weather: &str, layer: &str) -> PathBuf {
+    let name = match weather {
+        "storm" => "storm-lightning",
+        name => name,
+    };
+    PathBuf::from(format!("static/img/weather/texture-{}-{}.png", name, layer))
+}
+
+fn load_png(path: &Path) -> Result<RgbaImage, String> {
+    let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+
+    let file = File::open(path).map_err(|e| error(&e))?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info().map_err(|e| error(&e))?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|e| error(&e))?;
+    let buf = &buf[..info.buffer_size()];
+
+    let data = match info.color_type {
+        png::ColorType::Rgba => buf.to_vec(),
+        png::ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        png::ColorType::Indexed => return Err(error(&"unsupported indexed color")),
+    };
+
+    RgbaImage::from_raw(info.width, info.height, data).ok_or_else(|| error(&"invalid image size"))
+}
+
+fn save_png(path: &Path, image: &RgbaImage) -> Result<(), String> {
+    let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+
+    let file = File::create(path).map_err(|e| error(&e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| error(&e))?;
+    writer
+        .write_image_data(&image.data)
+        .map_err(|e| error(&e))?;
+    writer.finish().map_err(|e| error(&e))
+}
+
+/// 输出格式：指定的格式，或按文件扩展名推断
+fn output_format(args: &Args) -> Result<&str, String> {
+    if let Some(format) = &args.format {
+        return match format.as_str() {
+            "png" | "gif" | "apng" | "ansi" | "ascii" => Ok(format),
+            _ => Err(format!("unknown format: {}", format)),
+        };
+    }
+    let extension = args
+        .output
+        .as_ref()
+        .and_then(|output| output.extension())
+        .and_then(|extension| extension.to_str());
+    Ok(match extension {
+        Some("gif") => "gif",
+        Some("apng") => "apng",
+        _ => "png",
+    })
+}
+
+fn save_animation(
+    path: &Path,
+    format: &str,
+    frames: &[RgbaImage],
+    args: &Args,
+) -> Result<(), String> {
+    let error = |e: &dyn std::fmt::Display| format!("{}: {}", path.display(), e);
+
+    let opts = AnimationOptions {
+        fps: args.fps,
+        colors: args.colors,
+        dither: args.dither,
+    };
+    let file = BufWriter::new(File::create(path).map_err(|e| error(&e))?);
+    match format {
+        "gif" => write_gif(file, frames, &opts),
+        _ => write_apng(file, frames, &opts),
+    }
+    .map_err(|e| error(&e))
+}
+
+/// 画到终端：ansi 在原处逐帧刷新并按帧率播放，ascii 依次输出每一帧，适合日志
+fn play_terminal(effect: &mut SoftwareEffect, format: &str, args: &Args) -> Result<(), String> {
+    let mode = match format {
+        "ansi" => TerminalMode::Ansi,
+        _ => TerminalMode::Ascii,
+    };
+    let time_scale = 60.0 / args.fps;
+    let interval = Duration::from_secs_f64(1.0 / args.fps);
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let error = |e: io::Error| format!("stdout: {}", e);
+
+    let start = Instant::now();
+    for i in 0..args.frames {
+        if i > 0 {
+            effect.step(time_scale);
+        }
+        let frame = effect.render_terminal(args.columns, args.rows, mode);
+        match mode {
+            TerminalMode::Ansi => {
+                // 第一帧前清屏，之后只把光标移回左上角
+                let clear = if i == 0 { "\x1b[2J" } else { "" };
+                write!(out, "{}\x1b[H{}", clear, frame).map_err(error)?;
+                out.flush().map_err(error)?;
+                if i + 1 < args.frames {
+                    let next = start + interval * (i + 1);
+                    thread::sleep(next.saturating_duration_since(Instant::now()));
+                }
+            }
+            TerminalMode::Ascii => {
+                if args.frames > 1 {
+                    writeln!(out, "-- frame {} --", i).map_err(error)?;
+                }
+                write!(out, "{}", frame).map_err(error)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let format = output_format(&args)?.to_owned();
+
+    let fg = args
+        .fg
+        .clone()
+        .unwrap_or_else(|| weather_image(&args.weather, "fg"));
+    let bg = args
+        .bg
+        .clone()
+        .unwrap_or_else(|| weather_image(&args.weather, "bg"));
+    let images = SoftwareImages {
+        fg: load_png(&fg)?,
+        bg: load_png(&bg)?,
+        drop_alpha: load_png(&args.drop_alpha)?,
+        drop_color: load_png(&args.drop_color)?,
+    };
+
+    // 打印种子，方便复现同一张图
+    let seed = args.seed.unwrap_or_else(rand::random);
+    eprintln!("seed: {}", seed);
+
+    let opts = SoftwareEffectOptions {
+        width: args.width,
+        height: args.height,
+        dpi: args.dpi,
+        seed: Some(seed),
+    };
+    let mut effect = SoftwareEffect::new(&args.weather, &images, Some(opts));
+    effect.warm_up(args.warm_up);
+    if format == "ansi" || format == "ascii" {
+        return play_terminal(&mut effect, &format, &args);
+    }
+    let time_scale = 60.0 / args.fps;
+
+    if format != "png" {
+        // 多渲染 loop_fade 帧用于淡入开头
+        let count = args.frames + args.loop_fade;
+        let mut frames = Vec::with_capacity(count as usize);
+        for i in 0..count {
+            if i > 0 {
+                effect.step(time_scale);
+            }
+            frames.push(effect.render());
+        }
+        let frames = seamless_loop(frames, args.loop_fade as usize);
+
+        let output = args
+            .output
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(format!("rain.{}", format)));
+        save_animation(&output, &format, &frames, &args)?;
+        eprintln!("wrote {} frames to {}", frames.len(), output.display());
+        return Ok(());
+    }
+
+    if args.frames == 1 {
+        let output = args.output.unwrap_or_else(|| PathBuf::from("rain.png"));
+        save_png(&output, &effect.render())?;
+        eprintln!("wrote {}", output.display());
+        return Ok(());
+    }
+
+    let output = args.output.unwrap_or_else(|| PathBuf::from("frames"));
+    fs::create_dir_all(&output).map_err(|e| format!("{}: {}", output.display(), e))?;
+    for i in 0..args.frames {
+        if i > 0 {
+            effect.step(time_scale);
+        }
+        let path = output.join(format!("frame-{:05}.png", i));
+        save_png(&path, &effect.render())?;
+    }
+    eprintln!("wrote {} frames to {}", args.frames, output.display());
+    Ok(())
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(args) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
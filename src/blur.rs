@@ -0,0 +1,124 @@
+use crate::rgba::RgbaImage;
+
+/// 背景模糊的参数
+///
+/// Example:
+/// ```rust
+/// let blur = BlurOptions {
+///     radius: 6.0,
+///     ..BlurOptions::new()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlurOptions {
+    /// 模糊半径（背景纹理的像素，约 3σ），0 为不模糊
+    pub radius: f64,
+    /// 进行模糊的分辨率比例 (0.0, 1.0]，越小越快，大半径时差别不明显
+    pub quality: f64,
+}
+
+impl Default for BlurOptions {
+    fn default() -> Self {
+        BlurOptions {
+            radius: 0.0,
+            quality: 1.0,
+        }
+    }
+}
+
+impl BlurOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+/// 归一化的一维高斯核，长度为 2 * ceil(radius) + 1
+///
+/// Example:
+/// ```rust
+/// let kernel = gaussian_kernel(3.0);
+/// assert_eq!(kernel.len(), 7);
+/// ```
+pub fn gaussian_kernel(radius: f64) -> Vec<f32> {
+    let r = radius.max(0.0).ceil() as i32;
+    if r == 0 {
+        return vec![1.0];
+    }
+    let sigma = radius / 3.0;
+    let weights: Vec<f64> = (-r..=r)
+        .map(|i| (-(i * i) as f64 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|w| (w / total) as f32).collect()
+}
+
+/// 可分离的高斯模糊，先水平后垂直，边缘取最近的像素
+///
+/// 颜色按不透明度加权，半透明的边缘不会变暗。
+///
+/// Example:
+/// ```rust
+/// let mut image = RgbaImage::filled(384, 256, [0, 0, 0, 255]);
+/// gaussian_blur(&mut image, 8.0);
+/// ```
+pub fn gaussian_blur(image: &mut RgbaImage, radius: f64) {
+    let (w, h) = (image.width as usize, image.height as usize);
+    let kernel = gaussian_kernel(radius);
+    if kernel.len() == 1 || w == 0 || h == 0 {
+        return;
+    }
+
+    // 预乘不透明度
+    let mut pixels: Vec<[f32; 4]> = image
+        .data
+        .chunks(4)
+        .map(|p| {
+            let a = p[3] as f32 / 255.0;
+            [
+                p[0] as f32 * a,
+                p[1] as f32 * a,
+                p[2] as f32 * a,
+                p[3] as f32,
+            ]
+        })
+        .collect();
+    let horizontal = convolve(&pixels, w, h, &kernel, (1, 0));
+    pixels = convolve(&horizontal, w, h, &kernel, (0, 1));
+
+    for (p, out) in pixels.iter().zip(image.data.chunks_mut(4)) {
+        let a = p[3] / 255.0;
+        let c = |v: f32| if a > 0.0 { v / a } else { 0.0 };
+        out[0] = c(p[0]).round().clamp(0.0, 255.0) as u8;
+        out[1] = c(p[1]).round().clamp(0.0, 255.0) as u8;
+        out[2] = c(p[2]).round().clamp(0.0, 255.0) as u8;
+        out[3] = p[3].round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+// 沿 (dx, dy) 方向做一次一维卷积
+fn convolve(
+    pixels: &[[f32; 4]],
+    w: usize,
+    h: usize,
+    kernel: &[f32],
+    (dx, dy): (usize, usize),
+) -> Vec<[f32; 4]> {
+    let r = (kernel.len() / 2) as isize;
+    let mut out = vec![[0.0; 4]; pixels.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let mut sum = [0.0; 4];
+            for (k, weight) in kernel.iter().enumerate() {
+                let offset = k as isize - r;
+                let sx = (x as isize + offset * dx as isize).clamp(0, w as isize - 1) as usize;
+                let sy = (y as isize + offset * dy as isize).clamp(0, h as isize - 1) as usize;
+                let p = pixels[sy * w + sx];
+                for (s, v) in sum.iter_mut().zip(p) {
+                    *s += v * weight;
+                }
+            }
+            out[y * w + x] = sum;
+        }
+    }
+    out
+}
@@ -0,0 +1,174 @@
+use crate::create_canvas_element;
+use crate::rain_render::RainRenderOptions;
+use crate::textures::Texture;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+/// Canvas2D 渲染用到的纹理，与 WebGL 纹理单元一一对应
+pub struct CanvasTextures<'a> {
+    pub water_map: &'a Texture,
+    pub fg: &'a Texture,
+    pub bg: &'a Texture,
+    /// 双层玻璃中后层玻璃的水面纹理，None 时为单层玻璃
+    pub water_map_back: Option<&'a Texture>,
+    /// 过渡目标的前景/背景纹理，按 cross_fade 混合
+    pub next: (&'a Texture, &'a Texture),
+}
+
+/// 没有 WebGL 时使用的 Canvas2D 渲染
+///
+/// 只做近似的折射：先画背景，再把前景纹理按水面纹理的透明度裁剪后叠加，
+/// 雨滴里看到的是清晰、有视差的前景，不计算每个像素的折射方向。
+///
+/// Example:
+/// ```rust
+/// let render = CanvasRender::new(&canvas, &RainRenderOptions::window()).unwrap();
+/// render.draw(&textures, (0.0, 0.0), 0.0);
+/// ```
+pub struct CanvasRender {
+    ctx: CanvasRenderingContext2d,
+    // 雨滴图层
+    layer: Texture,
+    brightness: f64,
+    parallax_bg: f64,
+    parallax_fg: f64,
+    parallax_back: f64,
+}
+
+impl CanvasRender {
+    /// 画布已经有 WebGL 上下文时返回 None
+    pub fn new(canvas: &HtmlCanvasElement, opts: &RainRenderOptions) -> Option<Self> {
+        let ctx = canvas
+            .get_context("2d")
+            .ok()
+            .flatten()
+            .and_then(|ctx| ctx.dyn_into::<CanvasRenderingContext2d>().ok())?;
+        let (layer, layer_ctx) = create_canvas_element(canvas.width(), canvas.height()).ok()?;
+
+        Some(CanvasRender {
+            ctx,
+            layer: Texture {
+                canvas: layer,
+                ctx: layer_ctx,
+            },
+            brightness: opts.brightness,
+            parallax_bg: opts.parallax_bg,
+            parallax_fg: opts.parallax_fg,
+            parallax_back: opts.parallax_back,
+        })
+    }
+
+    pub fn set_brightness(&mut self, brightness: f64) {
+        self.brightness = brightness;
+    }
+
+    /// parallax 为视差（像素），cross_fade 为过渡目标的比例
+    pub fn draw(&self, textures: &CanvasTextures, parallax: (f64, f64), cross_fade: f64) {
+        let canvas = self.ctx.canvas().unwrap();
+        let (w, h) = (canvas.width(), canvas.height());
+        // 画布大小变化时同步雨滴图层
+        if self.layer.canvas.width() != w || self.layer.canvas.height() != h {
+            self.layer.canvas.set_width(w);
+            self.layer.canvas.set_height(h);
+        }
+        let (w, h) = (w as f64, h as f64);
+        let offset = |v: f64| (-parallax.0 * v, -parallax.1 * v);
+
+        let (next_fg, next_bg) = textures.next;
+        CanvasRender::draw_cover(
+            &self.ctx,
+            textures.bg,
+            next_bg,
+            cross_fade,
+            offset(self.parallax_bg),
+        );
+
+        // 先画后层玻璃，再画前层玻璃
+        let panes = [
+            (textures.water_map_back, self.parallax_back),
+            (Some(textures.water_map), self.parallax_fg),
+        ];
+        for (water_map, parallax) in panes {
+            if let Some(water_map) = water_map {
+                self.draw_drops(
+                    water_map,
+                    textures.fg,
+                    next_fg,
+                    cross_fade,
+                    offset(parallax),
+                );
+                self.ctx
+                    .draw_image_with_html_canvas_element_and_dw_and_dh(
+                        &self.layer.canvas,
+                        0.0,
+                        0.0,
+                        w,
+                        h,
+                    )
+                    .unwrap();
+            }
+        }
+    }
+
+    // 在雨滴图层上画出水面纹理范围内的前景：先画前景，再用水面纹理的透明度裁剪
+    fn draw_drops(
+        &self,
+        water_map: &Texture,
+        fg: &Texture,
+        next_fg: &Texture,
+        cross_fade: f64,
+        offset: (f64, f64),
+    ) {
+        let ctx = &self.layer.ctx;
+        let (w, h) = (
+            self.layer.canvas.width() as f64,
+            self.layer.canvas.height() as f64,
+        );
+
+        ctx.save();
+        ctx.clear_rect(0.0, 0.0, w, h);
+        ctx.set_filter(&format!("brightness({})", self.brightness));
+        CanvasRender::draw_cover(ctx, fg, next_fg, cross_fade, offset);
+        ctx.set_filter("none");
+        ctx.set_global_composite_operation("destination-in")
+            .unwrap();
+        ctx.draw_image_with_html_canvas_element_and_dw_and_dh(&water_map.canvas, 0.0, 0.0, w, h)
+            .unwrap();
+        ctx.restore();
+    }
+
+    /// 按比例放大并居中铺满画布（与着色器中的 scaledTexCoord 一致），并叠加过渡目标
+    fn draw_cover(
+        ctx: &CanvasRenderingContext2d,
+        texture: &Texture,
+        next: &Texture,
+        cross_fade: f64,
+        (x, y): (f64, f64),
+    ) {
+        let canvas = ctx.canvas().unwrap();
+        let (w, h) = (canvas.width() as f64, canvas.height() as f64);
+        let draw = |texture: &Texture| {
+            let (tw, th) = (
+                texture.canvas.width() as f64,
+                texture.canvas.height() as f64,
+            );
+            let scale = (w / tw).max(h / th);
+            let (dw, dh) = (tw * scale, th * scale);
+            ctx.draw_image_with_html_canvas_element_and_dw_and_dh(
+                &texture.canvas,
+                (w - dw) * 0.5 + x,
+                (h - dh) * 0.5 + y,
+                dw,
+                dh,
+            )
+            .unwrap();
+        };
+
+        draw(texture);
+        if cross_fade > 0.0 {
+            ctx.set_global_alpha(cross_fade);
+            draw(next);
+            ctx.set_global_alpha(1.0);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Event, HtmlCanvasElement};
+
+/// 监听画布的 `webglcontextlost` / `webglcontextrestored` 事件
+///
+/// 丢失时调用 `preventDefault`，浏览器之后才会尝试恢复上下文。监听在析构时移除。
+///
+/// Example:
+/// ```rust
+/// let listener = ContextLossListener::new(
+///     &canvas,
+///     move || animation.stop(),
+///     move || rain_render.borrow_mut().restore().unwrap(),
+/// );
+/// ```
+pub struct ContextLossListener {
+    canvas: HtmlCanvasElement,
+    on_lost: Closure<dyn FnMut(Event)>,
+    on_restored: Closure<dyn FnMut(Event)>,
+}
+
+impl ContextLossListener {
+    pub fn new(
+        canvas: &HtmlCanvasElement,
+        mut lost: impl FnMut() + 'static,
+        mut restored: impl FnMut() + 'static,
+    ) -> Self {
+        let on_lost = Closure::wrap(Box::new(move |event: Event| {
+            event.prevent_default();
+            lost();
+        }) as Box<dyn FnMut(Event)>);
+        let on_restored = Closure::wrap(Box::new(move |_: Event| {
+            restored();
+        }) as Box<dyn FnMut(Event)>);
+
+        canvas
+            .add_event_listener_with_callback("webglcontextlost", on_lost.as_ref().unchecked_ref())
+            .unwrap();
+        canvas
+            .add_event_listener_with_callback(
+                "webglcontextrestored",
+                on_restored.as_ref().unchecked_ref(),
+            )
+            .unwrap();
+
+        ContextLossListener {
+            canvas: canvas.clone(),
+            on_lost,
+            on_restored,
+        }
+    }
+}
+
+impl Drop for ContextLossListener {
+    fn drop(&mut self) {
+        let _ = self.canvas.remove_event_listener_with_callback(
+            "webglcontextlost",
+            self.on_lost.as_ref().unchecked_ref(),
+        );
+        let _ = self.canvas.remove_event_listener_with_callback(
+            "webglcontextrestored",
+            self.on_restored.as_ref().unchecked_ref(),
+        );
+    }
+}
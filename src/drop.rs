@@ -1,3 +1,4 @@
+use crate::trail::Trail;
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -15,6 +16,8 @@ pub struct Drop {
     pub is_new: bool,
     pub killed: bool,
     pub shrink: f64,
+    // 连续雨迹
+    pub trail: Option<Rc<RefCell<Trail>>>,
 }
 
 impl Default for Drop {
@@ -33,6 +36,7 @@ impl Default for Drop {
             is_new: true,
             killed: false,
             shrink: 0.0,
+            trail: None,
         }
     }
 }
@@ -0,0 +1,138 @@
+use crate::palette::Palette;
+use crate::rgba::RgbaImage;
+use std::io::{self, Write};
+
+/// 动画导出参数
+pub struct AnimationOptions {
+    /// 帧率
+    pub fps: f64,
+    /// GIF 调色板颜色数（2 ~ 256）
+    pub colors: usize,
+    /// GIF 是否使用误差扩散抖动
+    pub dither: bool,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        AnimationOptions {
+            fps: 30.0,
+            colors: 256,
+            dither: true,
+        }
+    }
+}
+
+impl AnimationOptions {
+    pub fn new() -> Self {
+        AnimationOptions::default()
+    }
+}
+
+/// 无缝循环：把多渲染的最后 fade 帧淡入到开头
+///
+/// 输入 N + fade 帧，输出 N 帧；第 k（k < fade）帧由第 N + k 帧逐渐过渡到第 k 帧，
+/// 所以最后一帧可以自然地接回第一帧。
+///
+/// Example:
+/// ```rust
+/// let frames = seamless_loop(frames, 15);
+/// ```
+pub fn seamless_loop(mut frames: Vec<RgbaImage>, fade: usize) -> Vec<RgbaImage> {
+    let fade = fade.min(frames.len() / 2);
+    if fade == 0 {
+        return frames;
+    }
+
+    let tail = frames.split_off(frames.len() - fade);
+    for (k, end) in tail.iter().enumerate() {
+        let t = k as f64 / fade as f64;
+        if let Some(frame) = end.mix(&frames[k], t) {
+            frames[k] = frame;
+        }
+    }
+    frames
+}
+
+// 第 i 帧的时长（1/100 秒），累计取整避免误差积累
+fn gif_delay(i: usize, fps: f64) -> u16 {
+    let at = |i: usize| (i as f64 * 100.0 / fps).round();
+    // 大多数浏览器会把小于 2 的延时当作 10
+    (at(i + 1) - at(i)).max(2.0) as u16
+}
+
+/// 写入循环播放的 GIF
+///
+/// 所有帧共用一个中位切分调色板。
+pub fn write_gif<W: Write>(w: W, frames: &[RgbaImage], opts: &AnimationOptions) -> io::Result<()> {
+    let first = match frames.first() {
+        Some(first) => first,
+        None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "no frames")),
+    };
+    let (width, height) = match (u16::try_from(first.width), u16::try_from(first.height)) {
+        (Ok(width), Ok(height)) => (width, height),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "GIF frames must be smaller than 65536 x 65536",
+            ))
+        }
+    };
+
+    let mut palette = Palette::median_cut(frames, opts.colors);
+    let mut encoder =
+        gif::Encoder::new(w, width, height, &palette.to_rgb_bytes()).map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+
+    for (i, frame) in frames.iter().enumerate() {
+        if frame.width != first.width || frame.height != first.height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "all frames must have the same size",
+            ));
+        }
+        let indices = palette.index(frame, opts.dither);
+        let frame = gif::Frame {
+            width,
+            height,
+            delay: gif_delay(i, opts.fps),
+            buffer: indices.into(),
+            ..gif::Frame::default()
+        };
+        encoder.write_frame(&frame).map_err(io::Error::other)?;
+    }
+    Ok(())
+}
+
+/// 写入循环播放的 APNG（真彩色，不丢失颜色）
+pub fn write_apng<W: Write>(w: W, frames: &[RgbaImage], opts: &AnimationOptions) -> io::Result<()> {
+    let first = match frames.first() {
+        Some(first) => first,
+        None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "no frames")),
+    };
+
+    let mut encoder = png::Encoder::new(w, first.width, first.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    // 0 为无限循环
+    encoder.set_animated(frames.len() as u32, 0)?;
+    // 每帧 100 / (fps * 100) 秒
+    encoder.set_frame_delay(
+        100,
+        (opts.fps * 100.0).round().clamp(1.0, u16::MAX as f64) as u16,
+    )?;
+
+    let mut writer = encoder.write_header()?;
+    for frame in frames {
+        if frame.width != first.width || frame.height != first.height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "all frames must have the same size",
+            ));
+        }
+        writer.write_image_data(&frame.data)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,703 @@
+use crate::images::ColorImage;
+use crate::water_map::WaterMap;
+use crate::webgl::Context;
+use js_sys::Float32Array;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use web_sys::{
+    console, WebGl2RenderingContext, WebGlBuffer, WebGlFramebuffer, WebGlProgram,
+    WebGlRenderingContext, WebGlTexture, WebGlUniformLocation, WebGlVertexArrayObject,
+};
+
+pub static WATER_MAP_VERTEX_SHADER: &str = r#"
+// corner of the unit quad
+attribute vec2 a_position;
+// per instance: x,y,w,h of the quad, or from.xy,to.xy of a streak
+attribute vec4 a_rect;
+// per instance: kind, depth, streak width, alpha
+attribute vec4 a_params;
+uniform vec2 u_size;
+varying vec2 v_uv;
+varying vec4 v_params;
+// streak length and half width
+varying vec2 v_streak;
+
+void main() {
+  vec2 pos;
+  if(a_params.x>2.5 && a_params.x<3.5){
+    vec2 d=a_rect.zw-a_rect.xy;
+    float len=length(d);
+    vec2 dir=len>0.0 ? d/len : vec2(1.0,0.0);
+    float halfWidth=a_params.z*0.5;
+    vec2 local=vec2(mix(-halfWidth,len+halfWidth,a_position.x),mix(-halfWidth,halfWidth,a_position.y));
+    pos=a_rect.xy+dir*local.x+vec2(-dir.y,dir.x)*local.y;
+    v_uv=local;
+    v_streak=vec2(len,halfWidth);
+  }else{
+    pos=a_rect.xy+a_position*a_rect.zw;
+    v_uv=a_position;
+    v_streak=vec2(0.0);
+  }
+  v_params=a_params;
+  // the first row of the texture is the top of the water map, as with a canvas upload
+  gl_Position=vec4(pos/u_size*2.0-1.0,0.0,1.0);
+}
+"#;
+
+pub static WATER_MAP_FRAGMENT_SHADER: &str = r#"
+precision mediump float;
+
+uniform sampler2D u_dropColor;
+uniform sampler2D u_dropAlpha;
+uniform sampler2D u_layer;
+varying vec2 v_uv;
+varying vec4 v_params;
+varying vec2 v_streak;
+
+// outputs premultiplied alpha
+void main() {
+  float kind=v_params.x;
+  if(kind<0.5){
+    // drop sprite: the color image with a screened blue overlay for depth
+    vec4 color=texture2D(u_dropColor,v_uv);
+    float a=texture2D(u_dropAlpha,v_uv).a;
+    gl_FragColor=vec4(color.r,color.g,1.0-(1.0-color.b)*(1.0-v_params.y),1.0)*a;
+  }else if(kind<1.5){
+    // ellipse inscribed in the quad
+    float d=length(v_uv*2.0-1.0);
+    gl_FragColor=vec4(1.0-smoothstep(0.96,1.0,d));
+  }else if(kind<2.5){
+    gl_FragColor=vec4(v_params.w);
+  }else if(kind<3.5){
+    // round-capped line
+    vec2 p=vec2(v_uv.x-clamp(v_uv.x,0.0,v_streak.x),v_uv.y);
+    float a=clamp(v_streak.y-length(p)+0.5,0.0,1.0)*v_params.w;
+    gl_FragColor=vec4(128.0/255.0,128.0/255.0,v_params.y,1.0)*a;
+  }else{
+    // alpha fades the layer out
+    gl_FragColor=texture2D(u_layer,v_uv)*v_params.w;
+  }
+}
+"#;
+
+// 实例种类，与着色器中的 kind 对应
+const KIND_DROP: f32 = 0.0;
+const KIND_ERASE: f32 = 1.0;
+const KIND_FADE: f32 = 2.0;
+const KIND_STREAK: f32 = 3.0;
+const KIND_LAYER: f32 = 4.0;
+
+// 纹理单元，与 `RainRender` 使用的 0 ~ 6 分开
+const COLOR_UNIT: u32 = 8;
+const ALPHA_UNIT: u32 = 9;
+const LAYER_UNIT: u32 = 10;
+
+// 每个实例的浮点数个数：a_rect + a_params
+const INSTANCE_FLOATS: usize = 8;
+
+/// 合成方式（纹理保存预乘透明度）
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Blend {
+    // source-over
+    Over,
+    // destination-out
+    Out,
+    // 直接覆盖
+    Copy,
+}
+
+// 合成方式相同的连续实例，一次绘制
+struct Batch {
+    blend: Blend,
+    instances: Vec<f32>,
+}
+
+/// 渲染目标：帧缓冲和作为颜色附件的纹理
+///
+/// 纹理的第一行为水面纹理的最上方，与上传 Canvas2D 画布时一致，可以直接替换画布作为 `u_waterMap`。
+/// 后处理的中间结果也使用同样的渲染目标。
+pub struct RenderTarget {
+    pub texture: WebGlTexture,
+    framebuffer: WebGlFramebuffer,
+    width: u32,
+    height: u32,
+}
+
+impl RenderTarget {
+    /// 创建 w x h 的 RGBA8 渲染目标（内容为透明），不支持时返回 None
+    pub fn new(gl: &WebGl2RenderingContext, w: u32, h: u32) -> Option<Self> {
+        let (w, h) = (w.max(1), h.max(1));
+        let texture = gl.create_texture()?;
+        gl.active_texture(WebGlRenderingContext::TEXTURE0 + LAYER_UNIT);
+        gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&texture));
+        set_texture_parameters(gl);
+        gl.tex_storage_2d(
+            WebGlRenderingContext::TEXTURE_2D,
+            1,
+            WebGl2RenderingContext::RGBA8,
+            w as i32,
+            h as i32,
+        );
+
+        let framebuffer = gl.create_framebuffer()?;
+        gl.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, Some(&framebuffer));
+        gl.framebuffer_texture_2d(
+            WebGlRenderingContext::FRAMEBUFFER,
+            WebGlRenderingContext::COLOR_ATTACHMENT0,
+            WebGlRenderingContext::TEXTURE_2D,
+            Some(&texture),
+            0,
+        );
+        let complete = gl.check_framebuffer_status(WebGlRenderingContext::FRAMEBUFFER)
+            == WebGlRenderingContext::FRAMEBUFFER_COMPLETE;
+        if complete {
+            gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
+        }
+        gl.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+
+        let target = RenderTarget {
+            texture,
+            framebuffer,
+            width: w,
+            height: h,
+        };
+        if !complete {
+            target.delete(gl);
+            return None;
+        }
+        Some(target)
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// 绑定帧缓冲，视口设为整个渲染目标
+    pub fn bind(&self, gl: &WebGl2RenderingContext) {
+        gl.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+        gl.viewport(0, 0, self.width as i32, self.height as i32);
+    }
+
+    pub fn delete(&self, gl: &WebGl2RenderingContext) {
+        gl.delete_framebuffer(Some(&self.framebuffer));
+        gl.delete_texture(Some(&self.texture));
+    }
+}
+
+/// 线性过滤、边缘截取
+pub fn set_texture_parameters(gl: &WebGl2RenderingContext) {
+    for (name, value) in [
+        (
+            WebGlRenderingContext::TEXTURE_WRAP_S,
+            WebGlRenderingContext::CLAMP_TO_EDGE,
+        ),
+        (
+            WebGlRenderingContext::TEXTURE_WRAP_T,
+            WebGlRenderingContext::CLAMP_TO_EDGE,
+        ),
+        (
+            WebGlRenderingContext::TEXTURE_MIN_FILTER,
+            WebGlRenderingContext::LINEAR,
+        ),
+        (
+            WebGlRenderingContext::TEXTURE_MAG_FILTER,
+            WebGlRenderingContext::LINEAR,
+        ),
+    ] {
+        gl.tex_parameteri(WebGlRenderingContext::TEXTURE_2D, name, value as i32);
+    }
+}
+
+// 着色器程序、缓冲区和雨滴图片，上下文丢失后重新创建
+struct SpriteResources {
+    program: WebGlProgram,
+    vao: Option<WebGlVertexArrayObject>,
+    quad: Option<WebGlBuffer>,
+    instances: Option<WebGlBuffer>,
+    color: Option<WebGlTexture>,
+    alpha: Option<WebGlTexture>,
+    size: Option<WebGlUniformLocation>,
+}
+
+/// GPU 上描画雨滴用的程序和雨滴图片，由同一上下文中的多张 `GpuWaterMap` 共用
+///
+/// 雨滴以实例化的四边形绘制：每个实例包含位置、大小和厚度，厚度在着色器中叠加，
+/// 不需要像 `CanvasSprites` 那样预先描画每种厚度的精灵。
+///
+/// Example:
+/// ```rust
+/// let sprites = Rc::new(GpuSprites::new(&gl, color_image)?);
+/// let water_map = GpuWaterMap::new(w, h, sprites).unwrap();
+/// ```
+pub struct GpuSprites {
+    gl: WebGl2RenderingContext,
+    color_image: Rc<ColorImage>,
+    resources: RefCell<Option<SpriteResources>>,
+}
+
+impl GpuSprites {
+    pub fn new(gl: &WebGl2RenderingContext, color_image: Rc<ColorImage>) -> Result<Self, String> {
+        let sprites = GpuSprites {
+            gl: gl.clone(),
+            color_image,
+            resources: RefCell::new(None),
+        };
+        sprites.restore()?;
+        Ok(sprites)
+    }
+
+    /// 创建（或在上下文恢复后重新创建）程序、缓冲区和雨滴图片纹理
+    ///
+    /// 共用的多张水面纹理都会调用，程序仍然有效时（已经恢复过）不再重新创建。
+    pub fn restore(&self) -> Result<(), String> {
+        let gl = &self.gl;
+        if let Some(resources) = self.resources.borrow().as_ref() {
+            if gl.is_program(Some(&resources.program)) {
+                return Ok(());
+            }
+        }
+        self.delete();
+
+        let context = Context::WebGl2(gl.clone());
+        let vert = context.compile_shader(
+            WebGlRenderingContext::VERTEX_SHADER,
+            WATER_MAP_VERTEX_SHADER,
+        )?;
+        let frag = context.compile_shader(
+            WebGlRenderingContext::FRAGMENT_SHADER,
+            WATER_MAP_FRAGMENT_SHADER,
+        )?;
+        let program = context.link_program(&vert, &frag)?;
+        gl.delete_shader(Some(&vert));
+        gl.delete_shader(Some(&frag));
+
+        gl.use_program(Some(&program));
+        for (name, unit) in [
+            ("u_dropColor", COLOR_UNIT),
+            ("u_dropAlpha", ALPHA_UNIT),
+            ("u_layer", LAYER_UNIT),
+        ] {
+            gl.uniform1i(
+                gl.get_uniform_location(&program, name).as_ref(),
+                unit as i32,
+            );
+        }
+        let size = gl.get_uniform_location(&program, "u_size");
+
+        // 顶点属性保存在自己的顶点数组对象中，不影响 `WebGl` 的状态
+        let vao = gl.create_vertex_array();
+        gl.bind_vertex_array(vao.as_ref());
+
+        let quad = gl.create_buffer();
+        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, quad.as_ref());
+        unsafe {
+            let vertices = Float32Array::view(&[0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 1.0]);
+            gl.buffer_data_with_array_buffer_view(
+                WebGlRenderingContext::ARRAY_BUFFER,
+                &vertices,
+                WebGlRenderingContext::STATIC_DRAW,
+            );
+        }
+        let a_position = gl.get_attrib_location(&program, "a_position") as u32;
+        gl.enable_vertex_attrib_array(a_position);
+        gl.vertex_attrib_pointer_with_i32(a_position, 2, WebGlRenderingContext::FLOAT, false, 0, 0);
+
+        let instances = gl.create_buffer();
+        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, instances.as_ref());
+        let stride = (INSTANCE_FLOATS * 4) as i32;
+        for (name, offset) in [("a_rect", 0), ("a_params", 16)] {
+            let location = gl.get_attrib_location(&program, name) as u32;
+            gl.enable_vertex_attrib_array(location);
+            gl.vertex_attrib_pointer_with_i32(
+                location,
+                4,
+                WebGlRenderingContext::FLOAT,
+                false,
+                stride,
+                offset,
+            );
+            gl.vertex_attrib_divisor(location, 1);
+        }
+        gl.bind_vertex_array(None);
+
+        // 与 `WebGl` 一致，上传时预乘透明度
+        gl.pixel_storei(WebGlRenderingContext::UNPACK_PREMULTIPLY_ALPHA_WEBGL, 1);
+        let upload = |unit: u32, image: &web_sys::HtmlImageElement| {
+            let texture = gl.create_texture();
+            gl.active_texture(WebGlRenderingContext::TEXTURE0 + unit);
+            gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, texture.as_ref());
+            set_texture_parameters(gl);
+            gl.tex_image_2d_with_u32_and_u32_and_html_image_element(
+                WebGlRenderingContext::TEXTURE_2D,
+                0,
+                WebGlRenderingContext::RGBA as i32,
+                WebGlRenderingContext::RGBA,
+                WebGlRenderingContext::UNSIGNED_BYTE,
+                image,
+            )
+            .map(|_| texture)
+            .map_err(|_| String::from("failed to upload the drop image"))
+        };
+        let color = upload(COLOR_UNIT, &self.color_image.color)?;
+        let alpha = upload(ALPHA_UNIT, &self.color_image.alpha)?;
+
+        *self.resources.borrow_mut() = Some(SpriteResources {
+            program,
+            vao,
+            quad,
+            instances,
+            color,
+            alpha,
+            size,
+        });
+        Ok(())
+    }
+
+    /// 把 batches 依次绘制到 target 上，layer 为 `KIND_LAYER` 实例采样的纹理
+    fn run(&self, target: &RenderTarget, batches: &[Batch], layer: Option<&WebGlTexture>) {
+        let resources = self.resources.borrow();
+        let resources = match resources.as_ref() {
+            Some(resources) => resources,
+            None => return,
+        };
+        let gl = &self.gl;
+
+        target.bind(gl);
+        gl.use_program(Some(&resources.program));
+        gl.bind_vertex_array(resources.vao.as_ref());
+        gl.uniform2f(
+            resources.size.as_ref(),
+            target.width as f32,
+            target.height as f32,
+        );
+        for (unit, texture) in [
+            (COLOR_UNIT, resources.color.as_ref()),
+            (ALPHA_UNIT, resources.alpha.as_ref()),
+            (LAYER_UNIT, layer),
+        ] {
+            gl.active_texture(WebGlRenderingContext::TEXTURE0 + unit);
+            gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, texture);
+        }
+        gl.bind_buffer(
+            WebGlRenderingContext::ARRAY_BUFFER,
+            resources.instances.as_ref(),
+        );
+
+        for batch in batches {
+            match batch.blend {
+                Blend::Over => {
+                    gl.enable(WebGlRenderingContext::BLEND);
+                    gl.blend_func(
+                        WebGlRenderingContext::ONE,
+                        WebGlRenderingContext::ONE_MINUS_SRC_ALPHA,
+                    );
+                }
+                Blend::Out => {
+                    gl.enable(WebGlRenderingContext::BLEND);
+                    gl.blend_func(
+                        WebGlRenderingContext::ZERO,
+                        WebGlRenderingContext::ONE_MINUS_SRC_ALPHA,
+                    );
+                }
+                Blend::Copy => gl.disable(WebGlRenderingContext::BLEND),
+            }
+            unsafe {
+                let instances = Float32Array::view(&batch.instances);
+                gl.buffer_data_with_array_buffer_view(
+                    WebGlRenderingContext::ARRAY_BUFFER,
+                    &instances,
+                    WebGlRenderingContext::STREAM_DRAW,
+                );
+            }
+            gl.draw_arrays_instanced(
+                WebGlRenderingContext::TRIANGLE_STRIP,
+                0,
+                4,
+                (batch.instances.len() / INSTANCE_FLOATS) as i32,
+            );
+        }
+
+        // 恢复画布的渲染状态
+        gl.disable(WebGlRenderingContext::BLEND);
+        gl.bind_vertex_array(None);
+        gl.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+        gl.viewport(0, 0, gl.drawing_buffer_width(), gl.drawing_buffer_height());
+    }
+
+    fn delete(&self) {
+        let gl = &self.gl;
+        if let Some(resources) = self.resources.borrow_mut().take() {
+            gl.delete_program(Some(&resources.program));
+            gl.delete_vertex_array(resources.vao.as_ref());
+            gl.delete_buffer(resources.quad.as_ref());
+            gl.delete_buffer(resources.instances.as_ref());
+            gl.delete_texture(resources.color.as_ref());
+            gl.delete_texture(resources.alpha.as_ref());
+        }
+    }
+}
+
+impl Drop for GpuSprites {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
+
+/// 在 GPU 上描画的水面纹理（WebGL2）
+///
+/// 描画操作先排队，`flush` 时按合成方式分批以实例化绘制提交到帧缓冲；纹理保存预乘透明度。
+/// `RainRender` 直接采样渲染目标的纹理，不经过 CPU 上传。
+///
+/// 由 `ping_pong` 创建的纹理（雨滴层）有两张渲染目标：淡出时把上一帧的内容按比例复制到另一张，
+/// 再在其上描画新的雨滴、擦除雨滴经过的路径，然后交换两张纹理。
+///
+/// Example:
+/// ```rust
+/// let mut water_map = GpuWaterMap::new(w, h, sprites).unwrap();
+/// water_map.clear();
+/// water_map.draw_drop(0.5, x, y, w, h);
+/// water_map.flush();
+/// rain_render.set_water_map(WaterMapSource::Gpu(water_map.target()));
+/// ```
+pub struct GpuWaterMap {
+    sprites: Rc<GpuSprites>,
+    target: Rc<RefCell<RenderTarget>>,
+    // 交替使用的另一张渲染目标
+    back: RefCell<Option<RenderTarget>>,
+    // 下一次提交时保留的比例（累计的淡出）
+    keep: Cell<f32>,
+    pending: RefCell<Vec<Batch>>,
+}
+
+impl GpuWaterMap {
+    /// 创建 w x h 的渲染目标，不支持时返回 None
+    pub fn new(w: u32, h: u32, sprites: Rc<GpuSprites>) -> Option<Self> {
+        let target = RenderTarget::new(&sprites.gl, w, h)?;
+        Some(GpuWaterMap {
+            sprites,
+            target: Rc::new(RefCell::new(target)),
+            back: RefCell::new(None),
+            keep: Cell::new(1.0),
+            pending: RefCell::new(Vec::new()),
+        })
+    }
+
+    /// 创建交替使用两张渲染目标的纹理，淡出由着色器在两张纹理之间复制完成
+    ///
+    /// Example:
+    /// ```rust
+    /// let droplets = GpuWaterMap::ping_pong(w, h, sprites).unwrap();
+    /// ```
+    pub fn ping_pong(w: u32, h: u32, sprites: Rc<GpuSprites>) -> Option<Self> {
+        let back = RenderTarget::new(&sprites.gl, w, h)?;
+        let water_map = GpuWaterMap::new(w, h, sprites)?;
+        *water_map.back.borrow_mut() = Some(back);
+        Some(water_map)
+    }
+
+    /// 渲染目标，大小变化或上下文恢复时原地替换
+    pub fn target(&self) -> Rc<RefCell<RenderTarget>> {
+        self.target.clone()
+    }
+
+    fn push(&self, blend: Blend, instance: [f32; INSTANCE_FLOATS]) {
+        let mut pending = self.pending.borrow_mut();
+        match pending.last_mut() {
+            Some(batch) if batch.blend == blend => batch.instances.extend_from_slice(&instance),
+            _ => pending.push(Batch {
+                blend,
+                instances: instance.to_vec(),
+            }),
+        }
+    }
+
+    // 铺满整张纹理的实例
+    fn full(&self, kind: f32, alpha: f32) -> [f32; INSTANCE_FLOATS] {
+        let target = self.target.borrow();
+        [
+            0.0,
+            0.0,
+            target.width as f32,
+            target.height as f32,
+            kind,
+            0.0,
+            0.0,
+            alpha,
+        ]
+    }
+
+    // 提交排队的描画
+    fn flush_pending(&self) {
+        let mut batches = self.pending.replace(Vec::new());
+        let keep = self.keep.replace(1.0);
+        let mut back = self.back.borrow_mut();
+        match back.as_mut() {
+            Some(back) if keep < 1.0 => {
+                // 上一帧的内容淡出后复制到另一张纹理，在其上描画，再交换
+                let copy = Batch {
+                    blend: Blend::Copy,
+                    instances: self.full(KIND_LAYER, keep).to_vec(),
+                };
+                batches.insert(0, copy);
+                let mut front = self.target.borrow_mut();
+                self.sprites.run(back, &batches, Some(&front.texture));
+                std::mem::swap(&mut *front, back);
+            }
+            _ if !batches.is_empty() => self.sprites.run(&self.target.borrow(), &batches, None),
+            _ => {}
+        }
+    }
+
+    /// 上下文恢复后重新创建程序和渲染目标（内容清空）
+    pub fn restore(&mut self) {
+        if let Err(err) = self.sprites.restore() {
+            console::error_1(&format!("failed to restore the water map: {}", err).into());
+        }
+        self.pending.borrow_mut().clear();
+        self.keep.set(1.0);
+        let (w, h) = {
+            let target = self.target.borrow();
+            (target.width, target.height)
+        };
+        let gl = &self.sprites.gl;
+        if let Some(target) = RenderTarget::new(gl, w, h) {
+            *self.target.borrow_mut() = target;
+        }
+        let mut back = self.back.borrow_mut();
+        if back.is_some() {
+            *back = RenderTarget::new(gl, w, h);
+        }
+    }
+}
+
+impl WaterMap for GpuWaterMap {
+    fn clear(&mut self) {
+        // 清空前排队的描画都会被清除
+        self.pending.borrow_mut().clear();
+        self.keep.set(1.0);
+        let gl = &self.sprites.gl;
+        gl.bind_framebuffer(
+            WebGlRenderingContext::FRAMEBUFFER,
+            Some(&self.target.borrow().framebuffer),
+        );
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
+        gl.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+    }
+
+    fn draw_drop(&mut self, depth: f64, x: f64, y: f64, w: f64, h: f64) {
+        // 与 `CanvasSprites` 的 255 级厚度一致
+        let depth = (depth.clamp(0.0, 1.0) * 254.0).floor() / 255.0;
+        self.push(
+            Blend::Over,
+            [
+                x as f32,
+                y as f32,
+                w as f32,
+                h as f32,
+                KIND_DROP,
+                depth as f32,
+                0.0,
+                1.0,
+            ],
+        );
+    }
+
+    fn erase(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.push(
+            Blend::Out,
+            [
+                x as f32, y as f32, w as f32, h as f32, KIND_ERASE, 0.0, 0.0, 1.0,
+            ],
+        );
+    }
+
+    fn fade(&mut self, alpha: f64) {
+        if self.back.borrow().is_none() {
+            self.push(Blend::Out, self.full(KIND_FADE, alpha as f32));
+            return;
+        }
+        // 淡出之前排队的描画先提交，淡出在下一次提交时完成
+        if !self.pending.borrow().is_empty() {
+            self.flush_pending();
+        }
+        self.keep
+            .set(self.keep.get() * (1.0 - alpha.clamp(0.0, 1.0) as f32));
+    }
+
+    fn draw_streak(&mut self, from: (f64, f64), to: (f64, f64), width: f64, depth: u8) {
+        self.push(
+            Blend::Over,
+            [
+                from.0 as f32,
+                from.1 as f32,
+                to.0 as f32,
+                to.1 as f32,
+                KIND_STREAK,
+                depth as f32 / 255.0,
+                width as f32,
+                0.75,
+            ],
+        );
+    }
+
+    fn draw_layer(&mut self, layer: &Self) {
+        self.flush_pending();
+        layer.flush_pending();
+        let batch = Batch {
+            blend: Blend::Over,
+            instances: self.full(KIND_LAYER, 1.0).to_vec(),
+        };
+        let layer = layer.target.borrow();
+        self.sprites
+            .run(&self.target.borrow(), &[batch], Some(&layer.texture));
+    }
+
+    fn resize(&mut self, w: u32, h: u32) {
+        let (old_w, old_h) = {
+            let target = self.target.borrow();
+            (target.width, target.height)
+        };
+        if (old_w, old_h) == (w.max(1), h.max(1)) {
+            return;
+        }
+        self.flush_pending();
+        let gl = &self.sprites.gl;
+        let target = match RenderTarget::new(gl, w, h) {
+            Some(target) => target,
+            None => return,
+        };
+
+        // 已有的内容拉伸到新的大小
+        let old = self.target.replace(target);
+        let copy = Batch {
+            blend: Blend::Copy,
+            instances: self.full(KIND_LAYER, 1.0).to_vec(),
+        };
+        self.sprites
+            .run(&self.target.borrow(), &[copy], Some(&old.texture));
+        old.delete(gl);
+
+        let mut back = self.back.borrow_mut();
+        if let Some(back) = back.as_mut() {
+            if let Some(target) = RenderTarget::new(gl, w, h) {
+                std::mem::replace(back, target).delete(gl);
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        self.flush_pending();
+    }
+}
+
+impl Drop for GpuWaterMap {
+    fn drop(&mut self) {
+        let gl = &self.sprites.gl;
+        self.target.borrow().delete(gl);
+        if let Some(back) = self.back.borrow_mut().take() {
+            back.delete(gl);
+        }
+    }
+}
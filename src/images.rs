@@ -5,6 +5,7 @@ use std::fmt;
 use std::rc::Rc;
 use web_sys::HtmlImageElement;
 
+#[derive(Clone)]
 pub struct Image {
     pub fg: HtmlImageElement,
     pub bg: HtmlImageElement,
@@ -15,6 +16,7 @@ pub struct ColorImage {
     pub color: HtmlImageElement,
 }
 
+#[derive(Clone)]
 pub enum WeatherImage {
     Rain(Image),
     Fallout(Image),
@@ -83,7 +85,8 @@ impl Images {
         }
     }
 
-    pub async fn change_weather(&self, value: &str) {
+    /// 加载天气图片，不影响当前天气
+    pub async fn load_weather(&self, value: &str) -> WeatherImage {
         let fg = value.to_owned() + "Fg";
         let path = self.values.get(&fg).unwrap();
         let fg = ImageFuture::new(path).await.unwrap();
@@ -94,6 +97,16 @@ impl Images {
 
         let img = Image { fg, bg };
 
-        *self.weather.borrow_mut() = WeatherImage::new(value, img);
+        WeatherImage::new(value, img)
+    }
+
+    /// 加载天气的闪电图片（`<天气>FlashFg` 和 `<天气>FlashBg`），没有配置时返回 None
+    pub async fn load_flash(&self, value: &str) -> Option<WeatherImage> {
+        let fg = self.values.get(&(value.to_owned() + "FlashFg"))?;
+        let bg = self.values.get(&(value.to_owned() + "FlashBg"))?;
+        let fg = ImageFuture::new(fg).await.ok()?;
+        let bg = ImageFuture::new(bg).await.ok()?;
+
+        Some(WeatherImage::new(value, Image { fg, bg }))
     }
 }
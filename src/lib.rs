@@ -1,14 +1,43 @@
-mod drop;
+mod animation;
+pub mod blur;
+mod canvas_render;
+mod context_loss;
+pub mod drop;
+#[cfg(feature = "export")]
+pub mod export;
+mod gpu_water_map;
 mod image_future;
 mod images;
+pub mod lightning;
+pub mod noise;
+pub mod palette;
+pub mod parallax;
+mod parallax_input;
+mod post_process;
 mod rain_drops;
 mod rain_effect;
 mod rain_render;
+mod resize;
+pub mod rgba;
+pub mod safety;
 mod shader;
+pub mod shine;
+pub mod software_effect;
+pub mod software_render;
+mod software_water_map;
+pub mod spawn;
+pub mod terminal;
 mod textures;
-mod weather;
+pub mod trail;
+pub mod transition;
+mod video;
+mod water_map;
+pub mod weather;
 mod webgl;
 
+pub use rain_effect::RainEffect;
+pub use rain_render::RainRenderOptions;
+
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
@@ -96,11 +125,23 @@ pub fn create_canvas_element(
 /// request_animation_frame(g.borrow().as_ref().unwrap());
 /// ```
 ///
-pub fn request_animation_frame(f: &Closure<dyn FnMut()>) {
+pub fn request_animation_frame(f: &Closure<dyn FnMut()>) -> i32 {
     window()
         .unwrap()
         .request_animation_frame(f.as_ref().unchecked_ref())
-        .unwrap();
+        .unwrap()
+}
+
+/// cancel animation frame
+///
+/// Example:
+/// ```rust
+/// let handle = request_animation_frame(&closure);
+/// cancel_animation_frame(handle);
+/// ```
+///
+pub fn cancel_animation_frame(handle: i32) {
+    window().unwrap().cancel_animation_frame(handle).unwrap();
 }
 
 /// 可以获取到当前页面中与性能相关的信息
@@ -0,0 +1,130 @@
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+
+// 判定是否打闪的间隔（毫秒）
+const CHECK_INTERVAL: f64 = 500.0;
+
+// 包络的关键帧：相对闪电开始的时刻（毫秒）和亮度
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Key {
+    time: f64,
+    value: f64,
+}
+
+/// 一次闪电的亮度包络
+///
+/// 迅速亮起，明暗闪烁几次，再亮一次后逐渐熄灭；关键帧之间按五次方缓出。
+///
+/// Example:
+/// ```rust
+/// let flash = Flash::new(now(), &mut rng);
+/// let value = flash.value(now());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Flash {
+    start: f64,
+    keys: Vec<Key>,
+}
+
+impl Flash {
+    pub fn new(start: f64, rng: &mut dyn RngCore) -> Self {
+        let mut keys = vec![Key {
+            time: 0.0,
+            value: 0.0,
+        }];
+        let mut push = |duration: f64, value: f64| {
+            let time = keys.last().map_or(0.0, |key| key.time) + duration;
+            keys.push(Key { time, value });
+        };
+
+        push(25.0, 1.0);
+        for _ in 0..rng.gen_range(2..=7) {
+            push(25.0, rng.gen_range(0.1..1.0));
+        }
+        push(100.0, 1.0);
+        push(250.0, 0.0);
+
+        Flash { start, keys }
+    }
+
+    /// 持续时间（毫秒）
+    pub fn duration(&self) -> f64 {
+        self.keys.last().map_or(0.0, |key| key.time)
+    }
+
+    pub fn is_finished(&self, now: f64) -> bool {
+        now - self.start >= self.duration()
+    }
+
+    /// now 时刻的亮度（0.0 ~ 1.0）
+    pub fn value(&self, now: f64) -> f64 {
+        let t = now - self.start;
+        let i = self.keys.partition_point(|key| key.time <= t);
+        if i == 0 || i >= self.keys.len() {
+            return 0.0;
+        }
+        let (from, to) = (self.keys[i - 1], self.keys[i]);
+        let p = (t - from.time) / (to.time - from.time);
+        let eased = 1.0 - (1.0 - p).powi(5);
+        from.value + (to.value - from.value) * eased
+    }
+}
+
+/// 闪电，每 500 毫秒按天气的 `flash_chance` 判定一次是否打闪
+///
+/// Example:
+/// ```rust
+/// let mut lightning = Lightning::new(None);
+/// let value = lightning.update(now(), weather.options().flash_chance);
+/// ```
+pub struct Lightning {
+    rng: StdRng,
+    flash: Option<Flash>,
+    next_check: Option<f64>,
+}
+
+impl Lightning {
+    /// seed 为 None 时使用随机种子
+    pub fn new(seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Lightning {
+            rng,
+            flash: None,
+            next_check: None,
+        }
+    }
+
+    /// 立即打闪，正在打闪时重新开始
+    pub fn trigger(&mut self, now: f64) {
+        self.flash = Some(Flash::new(now, &mut self.rng));
+    }
+
+    pub fn is_flashing(&self) -> bool {
+        self.flash.is_some()
+    }
+
+    /// 推进到 now，返回当前的亮度；chance 为每次判定打闪的概率
+    ///
+    /// 暂停后再继续时只补一次判定。
+    pub fn update(&mut self, now: f64, chance: f64) -> f64 {
+        let next_check = *self.next_check.get_or_insert(now + CHECK_INTERVAL);
+        if now >= next_check {
+            self.next_check = Some(now + CHECK_INTERVAL);
+            if self.flash.is_none() && chance > 0.0 && self.rng.gen::<f64>() < chance {
+                self.trigger(now);
+            }
+        }
+
+        match &self.flash {
+            Some(flash) if flash.is_finished(now) => {
+                self.flash = None;
+                0.0
+            }
+            Some(flash) => flash.value(now),
+            None => 0.0,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+/// 梯度噪声（Improved Perlin Noise）
+///
+/// 在空间和时间上连续变化，用来生成一阵一阵的雨。
+///
+/// Example:
+/// ```rust
+/// let noise = Noise::new(42);
+/// let n = noise.fbm(x / 500.0, y / 500.0, t, 3);
+/// ```
+pub struct Noise {
+    perm: [u8; 512],
+}
+
+impl Noise {
+    pub fn new(seed: u64) -> Self {
+        let mut table = [0u8; 256];
+        for (i, v) in table.iter_mut().enumerate() {
+            *v = i as u8;
+        }
+
+        // xorshift 洗牌，同一个种子得到同一张排列表
+        let mut state = seed ^ 0x9e37_79b9_7f4a_7c15;
+        for i in (1..256).rev() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let j = (state % (i as u64 + 1)) as usize;
+            table.swap(i, j);
+        }
+
+        let mut perm = [0u8; 512];
+        for (i, v) in perm.iter_mut().enumerate() {
+            *v = table[i & 255];
+        }
+
+        Noise { perm }
+    }
+
+    /// 三维噪声，取值范围约为 -1.0 ~ 1.0
+    pub fn noise(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (xf, yf, zf) = (x.floor(), y.floor(), z.floor());
+        let (xi, yi, zi) = (
+            (xf as i64 & 255) as usize,
+            (yf as i64 & 255) as usize,
+            (zf as i64 & 255) as usize,
+        );
+        let (x, y, z) = (x - xf, y - yf, z - zf);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let p = &self.perm;
+        let a = p[xi] as usize + yi;
+        let aa = p[a] as usize + zi;
+        let ab = p[a + 1] as usize + zi;
+        let b = p[xi + 1] as usize + yi;
+        let ba = p[b] as usize + zi;
+        let bb = p[b + 1] as usize + zi;
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1.0, y, z)),
+                lerp(
+                    u,
+                    grad(p[ab], x, y - 1.0, z),
+                    grad(p[bb], x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.0),
+                    grad(p[ba + 1], x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1.0, z - 1.0),
+                    grad(p[bb + 1], x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+
+    /// 分形噪声：叠加 octaves 层频率加倍、振幅减半的噪声，结果归一化到 -1.0 ~ 1.0
+    pub fn fbm(&self, x: f64, y: f64, z: f64, octaves: u32) -> f64 {
+        let (mut sum, mut amplitude, mut frequency, mut total) = (0.0, 1.0, 1.0, 0.0);
+        for _ in 0..octaves.max(1) {
+            sum += self.noise(x * frequency, y * frequency, z * frequency) * amplitude;
+            total += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum / total
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
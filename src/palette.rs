@@ -0,0 +1,173 @@
+use crate::rgba::RgbaImage;
+
+// 生成调色板时最多采样的像素数
+const MAX_SAMPLES: usize = 1 << 18;
+
+/// 索引色调色板（最多 256 色），用于 GIF 等索引色格式
+///
+/// Example:
+/// ```rust
+/// let palette = Palette::median_cut(&frames, 256);
+/// let indices = palette.index(&frames[0], true);
+/// ```
+pub struct Palette {
+    colors: Vec<[u8; 3]>,
+    // 按 RGB555 缓存的最近颜色
+    cache: Vec<u16>,
+}
+
+impl Palette {
+    /// 由颜色创建，超过 256 色时截断
+    pub fn new(mut colors: Vec<[u8; 3]>) -> Self {
+        colors.truncate(256);
+        if colors.is_empty() {
+            colors.push([0, 0, 0]);
+        }
+        Palette {
+            colors,
+            cache: vec![u16::MAX; 1 << 15],
+        }
+    }
+
+    /// 中位切分：从所有图片中采样，生成最多 max_colors 色的共用调色板
+    ///
+    /// 整段动画共用一个调色板，避免帧与帧之间颜色跳动。
+    pub fn median_cut(images: &[RgbaImage], max_colors: usize) -> Self {
+        let total: usize = images.iter().map(|image| image.data.len() / 4).sum();
+        let step = (total / MAX_SAMPLES).max(1);
+        let pixels: Vec<[u8; 3]> = images
+            .iter()
+            .flat_map(|image| image.data.chunks_exact(4))
+            .step_by(step)
+            .map(|p| [p[0], p[1], p[2]])
+            .collect();
+
+        let max_colors = max_colors.clamp(1, 256);
+        let mut boxes = vec![pixels];
+        while boxes.len() < max_colors {
+            // 切分颜色范围最大的盒子
+            let widest = boxes
+                .iter()
+                .enumerate()
+                .filter(|(_, pixels)| pixels.len() > 1)
+                .map(|(i, pixels)| (i, widest_channel(pixels)))
+                .max_by_key(|(_, (_, range))| *range);
+            let (i, channel) = match widest {
+                Some((i, (channel, range))) if range > 0 => (i, channel),
+                _ => break,
+            };
+
+            let mut pixels = boxes.swap_remove(i);
+            pixels.sort_unstable_by_key(|p| p[channel]);
+            // 在中位数处切分，相同的值留在同一侧
+            let median = pixels[pixels.len() / 2][channel];
+            let split = match pixels.partition_point(|p| p[channel] < median) {
+                0 => pixels.partition_point(|p| p[channel] <= median),
+                split => split,
+            };
+            let upper = pixels.split_off(split);
+            boxes.push(pixels);
+            boxes.push(upper);
+        }
+
+        let colors = boxes
+            .iter()
+            .filter(|pixels| !pixels.is_empty())
+            .map(|pixels| {
+                let mut sum = [0u64; 3];
+                for p in pixels {
+                    for c in 0..3 {
+                        sum[c] += p[c] as u64;
+                    }
+                }
+                sum.map(|s| (s / pixels.len() as u64) as u8)
+            })
+            .collect();
+
+        Palette::new(colors)
+    }
+
+    pub fn colors(&self) -> &[[u8; 3]] {
+        &self.colors
+    }
+
+    /// 调色板的 RGB 字节（r, g, b, r, g, b, ...）
+    pub fn to_rgb_bytes(&self) -> Vec<u8> {
+        self.colors.iter().flatten().copied().collect()
+    }
+
+    /// 最接近的颜色的索引
+    pub fn nearest(&mut self, [r, g, b]: [u8; 3]) -> u8 {
+        let key = ((r as usize >> 3) << 10) | ((g as usize >> 3) << 5) | (b as usize >> 3);
+        if self.cache[key] == u16::MAX {
+            // 以 RGB555 格子的中心为准，同一格子内的颜色共用结果
+            let center = [r | 4, g | 4, b | 4];
+            self.cache[key] = self.search(center) as u16;
+        }
+        self.cache[key] as u8
+    }
+
+    fn search(&self, color: [u8; 3]) -> usize {
+        let distance =
+            |c: &[u8; 3]| -> i32 { (0..3).map(|i| (c[i] as i32 - color[i] as i32).pow(2)).sum() };
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, c)| distance(c))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    /// 把图片转换为索引，dither 为 true 时使用 Floyd–Steinberg 误差扩散
+    pub fn index(&mut self, image: &RgbaImage, dither: bool) -> Vec<u8> {
+        let (w, h) = (image.width as usize, image.height as usize);
+        let mut indices = Vec::with_capacity(w * h);
+        // 当前行和下一行累积的误差
+        let mut error = vec![[0.0f32; 3]; w + 2];
+        let mut next_error = vec![[0.0f32; 3]; w + 2];
+
+        for y in 0..h {
+            for x in 0..w {
+                let i = (y * w + x) * 4;
+                let mut color = [0u8; 3];
+                for c in 0..3 {
+                    let value = image.data[i + c] as f32 + error[x + 1][c];
+                    color[c] = value.round().clamp(0.0, 255.0) as u8;
+                }
+                let index = self.nearest(color);
+                indices.push(index);
+
+                if dither {
+                    let chosen = self.colors[index as usize];
+                    for c in 0..3 {
+                        let e = color[c] as f32 - chosen[c] as f32;
+                        error[x + 2][c] += e * 7.0 / 16.0;
+                        next_error[x][c] += e * 3.0 / 16.0;
+                        next_error[x + 1][c] += e * 5.0 / 16.0;
+                        next_error[x + 2][c] += e * 1.0 / 16.0;
+                    }
+                }
+            }
+            std::mem::swap(&mut error, &mut next_error);
+            next_error.iter_mut().for_each(|e| *e = [0.0; 3]);
+        }
+
+        indices
+    }
+}
+
+// 范围最大的颜色通道，返回 (通道, 范围)
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    let mut min = [u8::MAX; 3];
+    let mut max = [u8::MIN; 3];
+    for p in pixels {
+        for c in 0..3 {
+            min[c] = min[c].min(p[c]);
+            max[c] = max[c].max(p[c]);
+        }
+    }
+    (0..3)
+        .map(|c| (c, max[c] - min[c]))
+        .max_by_key(|(_, range)| *range)
+        .unwrap()
+}
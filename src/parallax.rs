@@ -0,0 +1,162 @@
+/// 视差的输入来源
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParallaxSource {
+    /// 鼠标或触摸的位置
+    Pointer,
+    /// 设备的倾斜（陀螺仪）
+    Orientation,
+    /// 页面的滚动位置
+    Scroll,
+}
+
+impl ParallaxSource {
+    pub const ALL: [ParallaxSource; 3] = [
+        ParallaxSource::Pointer,
+        ParallaxSource::Orientation,
+        ParallaxSource::Scroll,
+    ];
+
+    /// pointer / orientation / scroll
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pointer" => Some(ParallaxSource::Pointer),
+            "orientation" => Some(ParallaxSource::Orientation),
+            "scroll" => Some(ParallaxSource::Scroll),
+            _ => None,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// 鼠标位置换算为视差输入，画面中心为 0，边缘为 ±1
+pub fn pointer_input(x: f64, y: f64, width: f64, height: f64) -> (f64, f64) {
+    let axis = |v: f64, size: f64| (v / size.max(1.0) * 2.0 - 1.0).clamp(-1.0, 1.0);
+    (axis(x, width), axis(y, height))
+}
+
+// 手持设备时自然的前后倾斜角（度）和对应 ±1 的倾斜范围
+const ORIENTATION_REST: f64 = 45.0;
+const ORIENTATION_RANGE: f64 = 30.0;
+
+/// 设备倾斜换算为视差输入
+///
+/// gamma 为左右倾斜（-90 ~ 90 度），beta 为前后倾斜（-180 ~ 180 度），
+/// 以手持时约 45 度的前后倾斜为中心。
+pub fn orientation_input(beta: f64, gamma: f64) -> (f64, f64) {
+    (
+        (gamma / ORIENTATION_RANGE).clamp(-1.0, 1.0),
+        ((beta - ORIENTATION_REST) / ORIENTATION_RANGE).clamp(-1.0, 1.0),
+    )
+}
+
+/// 页面滚动换算为视差输入，滚动到开头为 -1，到末尾为 1；不能滚动的方向为 0
+pub fn scroll_input(scroll: (f64, f64), max_scroll: (f64, f64)) -> (f64, f64) {
+    let axis = |v: f64, max: f64| {
+        if max <= 0.0 {
+            0.0
+        } else {
+            (v / max * 2.0 - 1.0).clamp(-1.0, 1.0)
+        }
+    };
+    (axis(scroll.0, max_scroll.0), axis(scroll.1, max_scroll.1))
+}
+
+/// 由多个输入来源合成视差，按各来源的增益相加后平滑
+///
+/// 结果在 -1 ~ 1 之间，传给 `RainRender::set_parallax`。
+///
+/// Example:
+/// ```rust
+/// let mut parallax = Parallax::new();
+/// parallax.set_gain(ParallaxSource::Pointer, 1.0);
+/// parallax.set_input(ParallaxSource::Pointer, pointer_input(x, y, w, h));
+/// let (x, y) = parallax.update(now());
+/// ```
+pub struct Parallax {
+    // 各来源的增益，0 为不使用
+    gains: [f64; 3],
+    inputs: [(f64, f64); 3],
+    // 平滑的时间常数（毫秒），0 为不平滑
+    smoothing: f64,
+    current: (f64, f64),
+    last: Option<f64>,
+}
+
+impl Default for Parallax {
+    fn default() -> Self {
+        Parallax {
+            gains: [0.0; 3],
+            inputs: [(0.0, 0.0); 3],
+            smoothing: 150.0,
+            current: (0.0, 0.0),
+            last: None,
+        }
+    }
+}
+
+impl Parallax {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn gain(&self, source: ParallaxSource) -> f64 {
+        self.gains[source.index()]
+    }
+
+    /// 设置来源的增益，0 为不使用该来源
+    pub fn set_gain(&mut self, source: ParallaxSource, gain: f64) {
+        self.gains[source.index()] = gain;
+        if gain == 0.0 {
+            self.inputs[source.index()] = (0.0, 0.0);
+        }
+    }
+
+    /// 正在使用的来源
+    pub fn sources(&self) -> Vec<ParallaxSource> {
+        ParallaxSource::ALL
+            .into_iter()
+            .filter(|&source| self.gain(source) != 0.0)
+            .collect()
+    }
+
+    /// 平滑的时间常数（毫秒），越大越慢跟上输入
+    pub fn set_smoothing(&mut self, smoothing: f64) {
+        self.smoothing = smoothing.max(0.0);
+    }
+
+    pub fn set_input(&mut self, source: ParallaxSource, input: (f64, f64)) {
+        self.inputs[source.index()] = input;
+    }
+
+    /// 各来源按增益相加后的目标视差
+    pub fn target(&self) -> (f64, f64) {
+        let (x, y) = self
+            .gains
+            .iter()
+            .zip(self.inputs.iter())
+            .fold((0.0, 0.0), |(x, y), (gain, input)| {
+                (x + input.0 * gain, y + input.1 * gain)
+            });
+        (x.clamp(-1.0, 1.0), y.clamp(-1.0, 1.0))
+    }
+
+    /// 推进到 now（毫秒），返回平滑后的视差
+    pub fn update(&mut self, now: f64) -> (f64, f64) {
+        let dt = self.last.map_or(0.0, |last| (now - last).max(0.0));
+        self.last = Some(now);
+        let target = self.target();
+        let k = if self.smoothing > 0.0 {
+            1.0 - (-dt / self.smoothing).exp()
+        } else {
+            1.0
+        };
+        self.current = (
+            self.current.0 + (target.0 - self.current.0) * k,
+            self.current.1 + (target.1 - self.current.1) * k,
+        );
+        self.current
+    }
+}
@@ -0,0 +1,118 @@
+use crate::document;
+use crate::parallax::{orientation_input, pointer_input, scroll_input, Parallax, ParallaxSource};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, DeviceOrientationEvent, Event, MouseEvent, Window};
+
+type EventCallback = Closure<dyn FnMut(Event)>;
+
+/// 监听视差的输入：鼠标移动、设备倾斜和页面滚动
+///
+/// 只监听 `Parallax` 中增益不为 0 的来源，输入写回 `Parallax`，监听在析构时移除。
+/// iOS 上设备倾斜需要页面先在用户操作中调用 `DeviceOrientationEvent.requestPermission()`。
+///
+/// Example:
+/// ```rust
+/// let listener = ParallaxListener::new(parallax.clone());
+/// ```
+pub struct ParallaxListener {
+    window: Window,
+    listeners: Vec<(&'static str, EventCallback)>,
+}
+
+impl ParallaxListener {
+    pub fn new(parallax: Rc<RefCell<Parallax>>) -> Self {
+        let window = window().unwrap();
+        let sources = parallax.borrow().sources();
+
+        let mut listeners = Vec::new();
+        for source in sources {
+            let parallax = parallax.clone();
+            let (event, callback): (&'static str, Box<dyn FnMut(Event)>) = match source {
+                ParallaxSource::Pointer => (
+                    "pointermove",
+                    Box::new(move |event: Event| {
+                        let event = match event.dyn_into::<MouseEvent>() {
+                            Ok(event) => event,
+                            Err(_) => return,
+                        };
+                        let (w, h) = ParallaxListener::window_size();
+                        let input =
+                            pointer_input(event.client_x() as f64, event.client_y() as f64, w, h);
+                        parallax.borrow_mut().set_input(source, input);
+                    }),
+                ),
+                ParallaxSource::Orientation => (
+                    "deviceorientation",
+                    Box::new(move |event: Event| {
+                        let event = match event.dyn_into::<DeviceOrientationEvent>() {
+                            Ok(event) => event,
+                            Err(_) => return,
+                        };
+                        if let (Some(beta), Some(gamma)) = (event.beta(), event.gamma()) {
+                            let input = orientation_input(beta, gamma);
+                            parallax.borrow_mut().set_input(source, input);
+                        }
+                    }),
+                ),
+                ParallaxSource::Scroll => {
+                    // 先按当前的滚动位置设置一次
+                    parallax
+                        .borrow_mut()
+                        .set_input(source, ParallaxListener::scroll());
+                    (
+                        "scroll",
+                        Box::new(move |_: Event| {
+                            parallax
+                                .borrow_mut()
+                                .set_input(source, ParallaxListener::scroll());
+                        }),
+                    )
+                }
+            };
+            let callback = Closure::wrap(callback);
+            window
+                .add_event_listener_with_callback(event, callback.as_ref().unchecked_ref())
+                .unwrap();
+            listeners.push((event, callback));
+        }
+
+        ParallaxListener { window, listeners }
+    }
+
+    fn window_size() -> (f64, f64) {
+        let window = window().unwrap();
+        (
+            window.inner_width().unwrap().as_f64().unwrap(),
+            window.inner_height().unwrap().as_f64().unwrap(),
+        )
+    }
+
+    fn scroll() -> (f64, f64) {
+        let window = window().unwrap();
+        let (w, h) = ParallaxListener::window_size();
+        let (scroll_w, scroll_h) = match document().document_element() {
+            Some(root) => (root.scroll_width() as f64, root.scroll_height() as f64),
+            None => (w, h),
+        };
+        scroll_input(
+            (
+                window.scroll_x().unwrap_or(0.0),
+                window.scroll_y().unwrap_or(0.0),
+            ),
+            (scroll_w - w, scroll_h - h),
+        )
+    }
+}
+
+impl Drop for ParallaxListener {
+    fn drop(&mut self) {
+        for (event, callback) in &self.listeners {
+            let _ = self
+                .window
+                .remove_event_listener_with_callback(event, callback.as_ref().unchecked_ref());
+        }
+    }
+}
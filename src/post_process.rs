@@ -0,0 +1,572 @@
+use crate::gpu_water_map::{set_texture_parameters, RenderTarget};
+use crate::webgl::{Context, UniformType};
+use js_sys::Float32Array;
+use serde::Deserialize;
+use web_sys::{
+    HtmlImageElement, WebGl2RenderingContext, WebGlBuffer, WebGlProgram, WebGlRenderingContext,
+    WebGlTexture, WebGlUniformLocation, WebGlVertexArrayObject,
+};
+
+pub static POST_VERTEX_SHADER: &str = r#"
+attribute vec2 a_position;
+varying vec2 v_uv;
+void main() {
+  v_uv=a_position*0.5+0.5;
+  gl_Position=vec4(a_position,0.0,1.0);
+}
+"#;
+
+// prepended to every pass; the pass drawing to the canvas applies the red saturation limit
+pub static LIMIT_RED: &str = r#"
+precision highp float;
+// upper limit of R/(R+G+B), 1.0 turns the limit off
+uniform float u_maxRedSaturation;
+// moves saturated reds towards grey, keeping the brightness
+vec4 limitRed(vec4 c){
+  float sum=c.r+c.g+c.b;
+  float maxRed=u_maxRedSaturation*sum;
+  if(c.r>maxRed){
+    c.rgb=mix(c.rgb,vec3(sum/3.0),(c.r-maxRed)/(c.r-sum/3.0));
+  }
+  return c;
+}
+"#;
+
+// keeps the highlights above u_threshold
+pub static BRIGHT_SHADER: &str = r#"
+precision highp float;
+uniform sampler2D u_input;
+uniform float u_threshold;
+varying vec2 v_uv;
+void main() {
+  vec4 c=texture2D(u_input,v_uv);
+  float l=dot(c.rgb,vec3(0.2126,0.7152,0.0722));
+  gl_FragColor=vec4(c.rgb*smoothstep(u_threshold,1.0,l),1.0);
+}
+"#;
+
+// 9-tap gaussian along u_direction, using linear filtering between taps
+pub static BLUR_SHADER: &str = r#"
+precision highp float;
+uniform sampler2D u_input;
+uniform vec2 u_texel;
+uniform vec2 u_direction;
+varying vec2 v_uv;
+void main() {
+  vec2 d=u_texel*u_direction;
+  vec4 c=texture2D(u_input,v_uv)*0.2270270270;
+  c+=(texture2D(u_input,v_uv+d*1.3846153846)+texture2D(u_input,v_uv-d*1.3846153846))*0.3162162162;
+  c+=(texture2D(u_input,v_uv+d*3.2307692308)+texture2D(u_input,v_uv-d*3.2307692308))*0.0702702703;
+  gl_FragColor=c;
+}
+"#;
+
+pub static BLOOM_SHADER: &str = r#"
+precision highp float;
+uniform sampler2D u_input;
+uniform sampler2D u_bloom;
+uniform float u_intensity;
+varying vec2 v_uv;
+void main() {
+  vec4 c=texture2D(u_input,v_uv);
+  gl_FragColor=limitRed(vec4(c.rgb+texture2D(u_bloom,v_uv).rgb*u_intensity,c.a));
+}
+"#;
+
+pub static VIGNETTE_SHADER: &str = r#"
+precision highp float;
+uniform sampler2D u_input;
+uniform float u_strength;
+uniform float u_radius;
+varying vec2 v_uv;
+void main() {
+  vec4 c=texture2D(u_input,v_uv);
+  // 0.0 at the centre, 1.0 at the corners
+  float d=distance(v_uv,vec2(0.5))*1.41421356;
+  c.rgb*=1.0-u_strength*smoothstep(u_radius,1.0,d);
+  gl_FragColor=limitRed(c);
+}
+"#;
+
+pub static GRAIN_SHADER: &str = r#"
+precision highp float;
+uniform sampler2D u_input;
+uniform vec2 u_texel;
+uniform float u_amount;
+uniform float u_time;
+varying vec2 v_uv;
+float hash(vec2 p){
+  return fract(sin(dot(p,vec2(12.9898,78.233)))*43758.5453);
+}
+void main() {
+  vec4 c=texture2D(u_input,v_uv);
+  vec2 pixel=floor(v_uv/u_texel);
+  float n=hash(pixel+fract(u_time)*vec2(113.0,71.0))-0.5;
+  gl_FragColor=limitRed(vec4(c.rgb+n*u_amount,c.a));
+}
+"#;
+
+// u_lut is a strip of u_lutSize tiles: red across each tile, green down it, blue from tile to tile
+pub static LUT_SHADER: &str = r#"
+precision highp float;
+uniform sampler2D u_input;
+uniform sampler2D u_lut;
+uniform float u_lutSize;
+uniform float u_intensity;
+varying vec2 v_uv;
+void main() {
+  vec4 c=texture2D(u_input,v_uv);
+  float n=u_lutSize;
+  float b=clamp(c.b,0.0,1.0)*(n-1.0);
+  float b0=floor(b);
+  float b1=min(b0+1.0,n-1.0);
+  vec2 uv=vec2((clamp(c.r,0.0,1.0)*(n-1.0)+0.5)/(n*n),(clamp(c.g,0.0,1.0)*(n-1.0)+0.5)/n);
+  vec3 c0=texture2D(u_lut,uv+vec2(b0/n,0.0)).rgb;
+  vec3 c1=texture2D(u_lut,uv+vec2(b1/n,0.0)).rgb;
+  gl_FragColor=limitRed(vec4(mix(c.rgb,mix(c0,c1,b-b0),u_intensity),c.a));
+}
+"#;
+
+// 纹理单元，与 `RainRender` 和 `GpuWaterMap` 使用的分开
+const INPUT_UNIT: u32 = 11;
+const SECOND_UNIT: u32 = 12;
+
+/// 高光泛光
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct BloomOptions {
+    /// 亮度超过该值的部分产生泛光（0.0 ~ 1.0）
+    pub threshold: f32,
+    pub intensity: f32,
+    /// 模糊半径（半分辨率下的像素）
+    pub radius: f32,
+}
+
+impl Default for BloomOptions {
+    fn default() -> Self {
+        BloomOptions {
+            threshold: 0.7,
+            intensity: 0.6,
+            radius: 2.0,
+        }
+    }
+}
+
+/// 暗角
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct VignetteOptions {
+    /// 四角变暗的比例
+    pub strength: f32,
+    /// 开始变暗的位置（0.0 为中心，1.0 为四角）
+    pub radius: f32,
+}
+
+impl Default for VignetteOptions {
+    fn default() -> Self {
+        VignetteOptions {
+            strength: 0.4,
+            radius: 0.5,
+        }
+    }
+}
+
+/// 胶片颗粒，每帧变化
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct FilmGrainOptions {
+    pub amount: f32,
+}
+
+impl Default for FilmGrainOptions {
+    fn default() -> Self {
+        FilmGrainOptions { amount: 0.06 }
+    }
+}
+
+/// 颜色查找表（LUT）调色
+///
+/// 查找表为 N²xN 的横条：N 格按蓝色递增排列，每格内红色从左到右、绿色从上到下递增，
+/// 例如 256x16、1024x32。
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ColorGradingOptions {
+    /// 查找表图片的地址
+    pub src: String,
+    /// 与原色混合的比例（1.0 为完全使用查找表）
+    pub intensity: Option<f32>,
+    /// 加载完成的查找表
+    #[serde(skip)]
+    pub lut: Option<HtmlImageElement>,
+}
+
+/// 后处理效果，按顺序作用在雨的合成结果上
+///
+/// 从 JavaScript 配置时以 `type` 区分：
+///
+/// Example:
+/// ```javascript
+/// effect.set_post_effects([
+///   { type: "bloom", threshold: 0.7, intensity: 0.6 },
+///   { type: "color_grading", src: "img/lut.png" },
+///   { type: "vignette" },
+///   { type: "film_grain", amount: 0.05 },
+/// ]);
+/// ```
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PostEffect {
+    Bloom(BloomOptions),
+    Vignette(VignetteOptions),
+    FilmGrain(FilmGrainOptions),
+    ColorGrading(ColorGradingOptions),
+}
+
+// 通道的输入
+#[derive(Clone, Copy)]
+enum Source {
+    Target(usize),
+    Lut(usize),
+}
+
+// 一个全屏绘制：从输入纹理到输出（None 为画布）
+struct Pass {
+    program: WebGlProgram,
+    inputs: Vec<(u32, Source)>,
+    output: Option<usize>,
+    position: u32,
+    texel: Option<WebGlUniformLocation>,
+    time: Option<WebGlUniformLocation>,
+    max_red_saturation: Option<WebGlUniformLocation>,
+}
+
+/// 后处理通道图（WebGL2）
+///
+/// 雨的合成结果先画到场景渲染目标上，再依次经过每个效果的通道，最后一个通道画到画布上。
+/// 每个通道有自己的程序和 uniform，中间结果在全分辨率的两张渲染目标之间交替；
+/// 泛光在半分辨率下提取高光并模糊后再叠加。画到画布上的通道按 `set_max_red_saturation` 限制红色饱和度。
+///
+/// Example:
+/// ```rust
+/// let post = PostProcess::new(&gl, &effects, w, h)?;
+/// post.bind_scene();
+/// rain.draw();
+/// post.run(time);
+/// ```
+pub struct PostProcess {
+    gl: WebGl2RenderingContext,
+    vao: Option<WebGlVertexArrayObject>,
+    quad: Option<WebGlBuffer>,
+    passes: Vec<Pass>,
+    // 渲染目标和相对画布的缩放
+    targets: Vec<(RenderTarget, f32)>,
+    luts: Vec<WebGlTexture>,
+}
+
+impl PostProcess {
+    /// 按 effects 的顺序创建通道，effects 不能为空
+    pub fn new(
+        gl: &WebGl2RenderingContext,
+        effects: &[PostEffect],
+        w: u32,
+        h: u32,
+    ) -> Result<Self, String> {
+        let vao = gl.create_vertex_array();
+        gl.bind_vertex_array(vao.as_ref());
+        let quad = gl.create_buffer();
+        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, quad.as_ref());
+        unsafe {
+            let vertices = Float32Array::view(&[-1.0, -1.0, 1.0, -1.0, -1.0, 1.0, 1.0, 1.0]);
+            gl.buffer_data_with_array_buffer_view(
+                WebGlRenderingContext::ARRAY_BUFFER,
+                &vertices,
+                WebGlRenderingContext::STATIC_DRAW,
+            );
+        }
+        gl.bind_vertex_array(None);
+
+        let mut post = PostProcess {
+            gl: gl.clone(),
+            vao,
+            quad,
+            passes: Vec::new(),
+            targets: Vec::new(),
+            luts: Vec::new(),
+        };
+        post.build(effects)?;
+        post.resize(w, h)?;
+        Ok(post)
+    }
+
+    fn build(&mut self, effects: &[PostEffect]) -> Result<(), String> {
+        // 0 为场景，1 为交替使用的另一张全分辨率渲染目标
+        let scene = self.target(1.0)?;
+        let mut swap = None;
+        let mut bloom: Option<(usize, usize)> = None;
+        let mut current = scene;
+
+        for (i, effect) in effects.iter().enumerate() {
+            let output = if i + 1 == effects.len() {
+                None
+            } else if current == scene {
+                if swap.is_none() {
+                    swap = Some(self.target(1.0)?);
+                }
+                swap
+            } else {
+                Some(scene)
+            };
+            let input = (INPUT_UNIT, Source::Target(current));
+
+            match effect {
+                PostEffect::Bloom(opts) => {
+                    let (a, b) = match bloom {
+                        Some(targets) => targets,
+                        None => *bloom.insert((self.target(0.5)?, self.target(0.5)?)),
+                    };
+                    let r = opts.radius;
+                    self.pass(
+                        BRIGHT_SHADER,
+                        &[input],
+                        Some(a),
+                        &[("threshold", UniformType::F1(opts.threshold))],
+                    )?;
+                    self.pass(
+                        BLUR_SHADER,
+                        &[(INPUT_UNIT, Source::Target(a))],
+                        Some(b),
+                        &[("direction", UniformType::F2(r, 0.0))],
+                    )?;
+                    self.pass(
+                        BLUR_SHADER,
+                        &[(INPUT_UNIT, Source::Target(b))],
+                        Some(a),
+                        &[("direction", UniformType::F2(0.0, r))],
+                    )?;
+                    self.pass(
+                        BLOOM_SHADER,
+                        &[input, (SECOND_UNIT, Source::Target(a))],
+                        output,
+                        &[
+                            ("bloom", UniformType::I1(SECOND_UNIT as i32)),
+                            ("intensity", UniformType::F1(opts.intensity)),
+                        ],
+                    )?;
+                }
+                PostEffect::Vignette(opts) => {
+                    self.pass(
+                        VIGNETTE_SHADER,
+                        &[input],
+                        output,
+                        &[
+                            ("strength", UniformType::F1(opts.strength)),
+                            ("radius", UniformType::F1(opts.radius)),
+                        ],
+                    )?;
+                }
+                PostEffect::FilmGrain(opts) => {
+                    self.pass(
+                        GRAIN_SHADER,
+                        &[input],
+                        output,
+                        &[("amount", UniformType::F1(opts.amount))],
+                    )?;
+                }
+                PostEffect::ColorGrading(opts) => {
+                    let lut = opts
+                        .lut
+                        .as_ref()
+                        .ok_or_else(|| format!("LUT {} is not loaded", opts.src))?;
+                    let (index, size) = self.lut(lut)?;
+                    self.pass(
+                        LUT_SHADER,
+                        &[input, (SECOND_UNIT, Source::Lut(index))],
+                        output,
+                        &[
+                            ("lut", UniformType::I1(SECOND_UNIT as i32)),
+                            ("lutSize", UniformType::F1(size)),
+                            ("intensity", UniformType::F1(opts.intensity.unwrap_or(1.0))),
+                        ],
+                    )?;
+                }
+            }
+            current = output.unwrap_or(current);
+        }
+        Ok(())
+    }
+
+    // 登记一张渲染目标，resize 时按缩放调整大小
+    fn target(&mut self, scale: f32) -> Result<usize, String> {
+        let target = RenderTarget::new(&self.gl, 1, 1)
+            .ok_or_else(|| String::from("Unable to create render target"))?;
+        self.targets.push((target, scale));
+        Ok(self.targets.len() - 1)
+    }
+
+    // 上传查找表，返回序号和格数
+    fn lut(&mut self, image: &HtmlImageElement) -> Result<(usize, f32), String> {
+        let gl = &self.gl;
+        let size = image.natural_height();
+        if size < 2 || image.natural_width() != size * size {
+            return Err(format!(
+                "a LUT must be N²xN pixels, got {}x{}",
+                image.natural_width(),
+                size
+            ));
+        }
+        let texture = gl
+            .create_texture()
+            .ok_or_else(|| String::from("Unable to create texture"))?;
+        gl.active_texture(WebGlRenderingContext::TEXTURE0 + SECOND_UNIT);
+        gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&texture));
+        set_texture_parameters(gl);
+        gl.tex_image_2d_with_u32_and_u32_and_html_image_element(
+            WebGlRenderingContext::TEXTURE_2D,
+            0,
+            WebGlRenderingContext::RGBA as i32,
+            WebGlRenderingContext::RGBA,
+            WebGlRenderingContext::UNSIGNED_BYTE,
+            image,
+        )
+        .map_err(|_| String::from("failed to upload the LUT"))?;
+        self.luts.push(texture);
+        Ok((self.luts.len() - 1, size as f32))
+    }
+
+    // 编译一个通道，uniforms 为不随帧变化的 uniform（不含 u_ 前缀）
+    fn pass(
+        &mut self,
+        fragment: &str,
+        inputs: &[(u32, Source)],
+        output: Option<usize>,
+        uniforms: &[(&str, UniformType)],
+    ) -> Result<(), String> {
+        let gl = &self.gl;
+        let context = Context::WebGl2(gl.clone());
+        let vert =
+            context.compile_shader(WebGlRenderingContext::VERTEX_SHADER, POST_VERTEX_SHADER)?;
+        let frag = context.compile_shader(
+            WebGlRenderingContext::FRAGMENT_SHADER,
+            &format!("{}{}", LIMIT_RED, fragment),
+        )?;
+        let program = context.link_program(&vert, &frag)?;
+        gl.delete_shader(Some(&vert));
+        gl.delete_shader(Some(&frag));
+
+        gl.use_program(Some(&program));
+        let location = |name: &str| gl.get_uniform_location(&program, &format!("u_{}", name));
+        gl.uniform1i(location("input").as_ref(), INPUT_UNIT as i32);
+        gl.uniform1f(location("maxRedSaturation").as_ref(), 1.0);
+        for (name, value) in uniforms {
+            let location = location(name);
+            match *value {
+                UniformType::F1(x) => gl.uniform1f(location.as_ref(), x),
+                UniformType::I1(x) => gl.uniform1i(location.as_ref(), x),
+                UniformType::F2(x, y) => gl.uniform2f(location.as_ref(), x, y),
+            }
+        }
+
+        self.passes.push(Pass {
+            position: gl.get_attrib_location(&program, "a_position") as u32,
+            texel: location("texel"),
+            time: location("time"),
+            max_red_saturation: location("maxRedSaturation"),
+            program,
+            inputs: inputs.to_vec(),
+            output,
+        });
+        Ok(())
+    }
+
+    /// 画布大小变化后重新创建渲染目标
+    pub fn resize(&mut self, w: u32, h: u32) -> Result<(), String> {
+        let gl = &self.gl;
+        for (target, scale) in self.targets.iter_mut() {
+            let size = (
+                (w as f32 * *scale).ceil() as u32,
+                (h as f32 * *scale).ceil() as u32,
+            );
+            if target.size() == size {
+                continue;
+            }
+            let new = RenderTarget::new(gl, size.0, size.1)
+                .ok_or_else(|| String::from("Unable to create render target"))?;
+            std::mem::replace(target, new).delete(gl);
+        }
+        Ok(())
+    }
+
+    /// 限制画到画布上的红色饱和度 R / (R + G + B)，1.0 为不限制
+    pub fn set_max_red_saturation(&self, value: f32) {
+        let gl = &self.gl;
+        for pass in self.passes.iter().filter(|pass| pass.output.is_none()) {
+            gl.use_program(Some(&pass.program));
+            gl.uniform1f(pass.max_red_saturation.as_ref(), value);
+        }
+    }
+
+    /// 把之后的绘制（雨的合成）画到场景渲染目标上
+    pub fn bind_scene(&self) {
+        self.targets[0].0.bind(&self.gl);
+    }
+
+    /// 依次执行全部通道，最后一个通道画到画布上；time 为秒，用于每帧变化的效果
+    pub fn run(&self, time: f32) {
+        let gl = &self.gl;
+        gl.bind_vertex_array(self.vao.as_ref());
+        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, self.quad.as_ref());
+        for pass in &self.passes {
+            match pass.output {
+                Some(output) => self.targets[output].0.bind(gl),
+                None => {
+                    gl.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+                    gl.viewport(0, 0, gl.drawing_buffer_width(), gl.drawing_buffer_height());
+                }
+            }
+            gl.use_program(Some(&pass.program));
+            gl.enable_vertex_attrib_array(pass.position);
+            gl.vertex_attrib_pointer_with_i32(
+                pass.position,
+                2,
+                WebGlRenderingContext::FLOAT,
+                false,
+                0,
+                0,
+            );
+
+            for (unit, source) in &pass.inputs {
+                let texture = match *source {
+                    Source::Target(i) => &self.targets[i].0.texture,
+                    Source::Lut(i) => &self.luts[i],
+                };
+                gl.active_texture(WebGlRenderingContext::TEXTURE0 + unit);
+                gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(texture));
+            }
+            if let Some(&(_, Source::Target(input))) = pass.inputs.first() {
+                let (w, h) = self.targets[input].0.size();
+                gl.uniform2f(pass.texel.as_ref(), 1.0 / w as f32, 1.0 / h as f32);
+            }
+            gl.uniform1f(pass.time.as_ref(), time);
+
+            gl.draw_arrays(WebGlRenderingContext::TRIANGLE_STRIP, 0, 4);
+        }
+        gl.bind_vertex_array(None);
+    }
+}
+
+impl Drop for PostProcess {
+    fn drop(&mut self) {
+        let gl = &self.gl;
+        for pass in &self.passes {
+            gl.delete_program(Some(&pass.program));
+        }
+        for (target, _) in &self.targets {
+            target.delete(gl);
+        }
+        for lut in &self.luts {
+            gl.delete_texture(Some(lut));
+        }
+        gl.delete_buffer(self.quad.as_ref());
+        gl.delete_vertex_array(self.vao.as_ref());
+    }
+}
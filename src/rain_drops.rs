@@ -1,17 +1,19 @@
 use crate::drop::Drop;
+use crate::gpu_water_map::{GpuSprites, GpuWaterMap};
 use crate::images::ColorImage;
-use crate::textures::Texture;
+use crate::noise::Noise;
+use crate::now;
+use crate::spawn::{Spawn, SpawnArea, SpawnStrategy, UniformSpawn};
+use crate::trail::{Trail, TrailMode};
+use crate::water_map::{CanvasSprites, CanvasWaterMap, WaterMap, WaterMapSource, WebWaterMap};
 use crate::weather::WeatherOptions;
-use crate::{create_canvas_element, now};
-use js_sys::Math::{max, min};
-use rand::{thread_rng, Rng};
-use std::cell::{RefCell, RefMut};
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use std::cell::{Ref, RefCell, RefMut};
 use std::f64::consts::PI;
 use std::rc::Rc;
 use wasm_bindgen::JsValue;
-use web_sys::{console, CanvasRenderingContext2d, HtmlCanvasElement};
-
-const DROP_SIZE: u32 = 64;
+use web_sys::{console, WebGl2RenderingContext};
 
 pub struct RainDropsOptions {
     /// 时标比率（time_scale *= time_scale_multiplier）
@@ -47,14 +49,30 @@ pub struct RainDropsOptions {
 
     /// 雨点生成区域
     pub spawn_area: [f64; 2],
+    /// 雨点生成策略
+    pub spawn: Rc<RefCell<dyn SpawnStrategy>>,
     pub auto_shrink: bool,
     pub trail_rate: f64,
     pub trail_scale_range: [f64; 2],
+    /// 雨迹样式
+    pub trail_mode: TrailMode,
+    /// 连续雨迹断裂成水珠的时间（帧）
+    pub trail_bead_age: f64,
     pub global_time_scale: f64,
     pub collision_radius: f64,
     pub collision_radius_increase: f64,
     pub collision_boost_multiplier: f64,
     pub collision_boost: f64,
+
+    /// 阵风强度（0 为关闭，雨量、雨滴密度和大小随噪声起伏）
+    pub gust_strength: f64,
+    /// 阵风噪声的空间尺度（像素）
+    pub gust_scale: f64,
+    /// 阵风移动速度（每帧移动的噪声单位）
+    pub gust_speed: f64,
+
+    /// 随机数种子（None 时每次运行都不同）
+    pub seed: Option<u64>,
 }
 
 impl Default for RainDropsOptions {
@@ -69,16 +87,23 @@ impl Default for RainDropsOptions {
             rain_limit: 3.0,
             rain_chance: 0.3,
             spawn_area: [-0.1, 0.95],
+            spawn: Rc::new(RefCell::new(UniformSpawn)),
             auto_shrink: true,
             trail_rate: 1.0,
             max_drops: 900,
             trail_scale_range: [0.2, 0.5],
+            trail_mode: TrailMode::Drops,
+            trail_bead_age: 180.0,
             global_time_scale: 1.0,
             collision_radius: 0.65,
             collision_radius_increase: 0.01,
             collision_boost_multiplier: 0.05,
             collision_boost: 1.0,
             droplets_cleaning_radius_multiplier: 0.43,
+            gust_strength: 0.0,
+            gust_scale: 500.0,
+            gust_speed: 0.004,
+            seed: None,
         }
     }
 }
@@ -87,9 +112,38 @@ impl RainDropsOptions {
     pub fn new() -> Self {
         RainDropsOptions::default()
     }
+
+    /// 窗户玻璃上的雨滴（页面默认效果）
+    pub fn window() -> Self {
+        RainDropsOptions {
+            trail_rate: 1.0,
+            trail_scale_range: [0.2, 0.45],
+            collision_radius: 0.45,
+            droplets_cleaning_radius_multiplier: 0.28,
+            ..RainDropsOptions::new()
+        }
+    }
+
+    /// 双层玻璃之间的冷凝水：雨点小、以细密的水珠为主，下滑较少
+    pub fn condensation() -> Self {
+        RainDropsOptions {
+            r: (6.0, 22.0),
+            droplets_rate: 30.0,
+            droplets_size: (1.5, 3.0),
+            drop_fall_multiplier: 1.5,
+            rain_limit: 1.0,
+            rain_chance: 0.1,
+            spawn_area: [0.0, 1.0],
+            trail_rate: 0.5,
+            trail_scale_range: [0.15, 0.3],
+            collision_radius: 0.45,
+            droplets_cleaning_radius_multiplier: 0.28,
+            ..RainDropsOptions::default()
+        }
+    }
 }
 
-pub struct RainDrops {
+pub struct RainDrops<M: WaterMap = WebWaterMap> {
     // 全局配置项
     opts: RainDropsOptions,
     // 背景宽度
@@ -98,36 +152,128 @@ pub struct RainDrops {
     height: f64,
     // 缩放比例
     scale: f64,
-    // 背景纹理
-    pub texture: Rc<RefCell<Texture>>,
-    // 上一次描画时间
-    last_time: f64,
+    // 水面纹理
+    water_map: RefCell<M>,
+    // 上一次描画时间（None 时下一帧按一帧计算）
+    last_time: Option<f64>,
     // 纹理清理迭代
     cleaning_iterations: f64,
 
     // 雨滴纹理
-    droplets: Texture,
+    droplets: RefCell<M>,
     // 雨滴像素密度
     droplets_pixel_density: f64,
     // 雨滴个数
     droplets_counter: u32,
-    // 雨滴纹理图片
-    color_image: Rc<ColorImage>,
 
     // 雨滴
     drops: Vec<Rc<RefCell<Drop>>>,
-    // 雨滴下落画布
-    drops_gfx: Vec<HtmlCanvasElement>,
-    // 雨滴清理画布
-    clear_gfx: Option<HtmlCanvasElement>,
+    // 连续雨迹
+    trails: Vec<Rc<RefCell<Trail>>>,
+
+    // 阵风噪声
+    noise: Noise,
+    // 累计时标
+    elapsed: f64,
+    // 是否描画到纹理（预热时关闭）
+    rendering: bool,
+    // 随机数
+    rng: RefCell<StdRng>,
 }
 
+/// 水滴像素密度： 默认1像素
+const DROPLETS_PIXEL_DENSITY: f64 = 1.0;
+
 impl RainDrops {
+    /// 创建浏览器中的雨滴模拟
+    ///
+    /// gl 为 WebGL2 上下文时，水面纹理在 GPU 上以实例化绘制描画，雨滴层在两张渲染目标之间
+    /// 交替更新，都不经过 CPU；否则（WebGL1、Canvas2D）由 Canvas2D 描画后上传。
+    ///
+    /// Example:
+    /// ```rust
+    /// let mut rain_drops = RainDrops::new(w, h, dpi, color_image, rain_render.webgl2().as_ref(), None);
+    /// rain_drops.render_droplets().unwrap();
+    /// rain_render.set_water_map(rain_drops.source());
+    /// ```
     pub fn new(
         w: f64,
         h: f64,
         scale: f64,
         color_image: Rc<ColorImage>,
+        gl: Option<&WebGl2RenderingContext>,
+        opts: Option<RainDropsOptions>,
+    ) -> Self {
+        let sprites = Rc::new(RefCell::new(CanvasSprites::new(color_image.clone())));
+        let gpu = gl.and_then(|gl| match GpuSprites::new(gl, color_image) {
+            Ok(gpu) => Some(Rc::new(gpu)),
+            Err(err) => {
+                console::warn_1(&format!("GPU water map unavailable: {}", err).into());
+                None
+            }
+        });
+        // 根据像素密度创建雨滴画布
+        let (droplets_w, droplets_h) = (
+            (w * DROPLETS_PIXEL_DENSITY) as u32,
+            (h * DROPLETS_PIXEL_DENSITY) as u32,
+        );
+        let maps = gpu.and_then(|gpu| {
+            Some((
+                GpuWaterMap::new(w as u32, h as u32, gpu.clone())?,
+                GpuWaterMap::ping_pong(droplets_w, droplets_h, gpu)?,
+            ))
+        });
+        let (texture, droplets) = match maps {
+            Some((texture, droplets)) => (WebWaterMap::Gpu(texture), WebWaterMap::Gpu(droplets)),
+            None => (
+                WebWaterMap::Canvas(CanvasWaterMap::new(w as u32, h as u32, sprites.clone())),
+                WebWaterMap::Canvas(CanvasWaterMap::new(droplets_w, droplets_h, sprites)),
+            ),
+        };
+
+        RainDrops::with_water_maps(w, h, scale, texture, droplets, opts)
+    }
+
+    pub fn render_droplets(&mut self) -> Result<(), JsValue> {
+        self.water_map.borrow().render_sprites()?;
+        self.droplets.borrow().render_sprites()
+    }
+
+    /// 渲染时使用的水面纹理
+    pub fn source(&self) -> WaterMapSource {
+        self.water_map.borrow().source()
+    }
+
+    /// WebGL 上下文恢复后重新创建 GPU 上的纹理，内容在下一帧重新描画
+    pub fn restore(&mut self) {
+        self.water_map.borrow_mut().restore();
+        self.droplets.borrow_mut().restore();
+    }
+}
+
+impl<M: WaterMap> RainDrops<M> {
+    /// 使用给定的水面纹理创建
+    ///
+    /// texture 为 w x h 的水面纹理，droplets 为按雨滴像素密度缩放的雨滴层。
+    ///
+    /// Example:
+    /// ```rust
+    /// let sprites = Rc::new(DropSprites::new(&drop_alpha, &drop_color));
+    /// let rain_drops = RainDrops::with_water_maps(
+    ///     w,
+    ///     h,
+    ///     1.0,
+    ///     SoftwareWaterMap::new(w as u32, h as u32, sprites.clone()),
+    ///     SoftwareWaterMap::new(w as u32, h as u32, sprites),
+    ///     None,
+    /// );
+    /// ```
+    pub fn with_water_maps(
+        w: f64,
+        h: f64,
+        scale: f64,
+        texture: M,
+        droplets: M,
         opts: Option<RainDropsOptions>,
     ) -> Self {
         // 初期化雨滴参数
@@ -136,142 +282,114 @@ impl RainDrops {
             None => RainDropsOptions::default(),
         };
 
-        // 水滴像素密度： 默认1像素
-        let droplets_pixel_density = 1.0;
-
-        // 创建背景画布
-        let (canvas, ctx) = create_canvas_element(w as u32, h as u32).unwrap();
-        // 根据像素密度创建雨滴画布
-        let (droplets, droplets_ctx) = create_canvas_element(
-            (w * droplets_pixel_density) as u32,
-            (h * droplets_pixel_density) as u32,
-        )
-        .unwrap();
-        let last_time = now();
+        let mut rng = match opts.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let noise = Noise::new(rng.gen());
         RainDrops {
             opts,
             scale,
             droplets_counter: 0,
             cleaning_iterations: 0.0,
-            last_time,
+            last_time: None,
             width: w,
             height: h,
-            droplets_pixel_density,
-            texture: Rc::new(RefCell::new(Texture { canvas, ctx })),
-            droplets: Texture {
-                canvas: droplets,
-                ctx: droplets_ctx,
-            },
-            color_image,
+            droplets_pixel_density: DROPLETS_PIXEL_DENSITY,
+            water_map: RefCell::new(texture),
+            droplets: RefCell::new(droplets),
             drops: Vec::new(),
-            drops_gfx: Vec::new(),
-            clear_gfx: None,
+            trails: Vec::new(),
+            noise,
+            elapsed: 0.0,
+            rendering: true,
+            rng: RefCell::new(rng),
         }
     }
 
-    pub fn render_droplets(&mut self) -> Result<(), JsValue> {
-        let (buf, buf_ctx) = create_canvas_element(DROP_SIZE, DROP_SIZE)?;
-
-        let values = (0..255).collect::<Vec<_>>();
-        self.drops_gfx = values
-            .iter()
-            .map(|i| {
-                let (drop, drop_ctx) = create_canvas_element(DROP_SIZE, DROP_SIZE).unwrap();
-
-                buf_ctx.clear_rect(0.0, 0.0, DROP_SIZE as f64, DROP_SIZE as f64);
-
-                // 颜色
-                buf_ctx
-                    .set_global_composite_operation("source-over")
-                    .unwrap();
-                buf_ctx
-                    .draw_image_with_html_image_element_and_dw_and_dh(
-                        &self.color_image.color,
-                        0.0,
-                        0.0,
-                        DROP_SIZE as f64,
-                        DROP_SIZE as f64,
-                    )
-                    .unwrap();
-
-                // blue overlay, for depth
-                buf_ctx.set_global_composite_operation("screen").unwrap();
-                buf_ctx.set_fill_style(&JsValue::from(format!("rgba(0,0,{},1)", i)));
-                buf_ctx.fill_rect(0.0, 0.0, DROP_SIZE as f64, DROP_SIZE as f64);
-
-                // alpha
-                drop_ctx
-                    .set_global_composite_operation("source-over")
-                    .unwrap();
-                drop_ctx
-                    .draw_image_with_html_image_element_and_dw_and_dh(
-                        &self.color_image.alpha,
-                        0.0,
-                        0.0,
-                        DROP_SIZE as f64,
-                        DROP_SIZE as f64,
-                    )
-                    .unwrap();
-
-                // color
-                drop_ctx
-                    .set_global_composite_operation("source-in")
-                    .unwrap();
-                drop_ctx
-                    .draw_image_with_html_canvas_element_and_dw_and_dh(
-                        &buf,
-                        0.0,
-                        0.0,
-                        DROP_SIZE as f64,
-                        DROP_SIZE as f64,
-                    )
-                    .unwrap();
-                drop
-            })
-            .collect::<Vec<HtmlCanvasElement>>();
-
-        let (clear, clear_ctx) = create_canvas_element(DROP_SIZE * 2, DROP_SIZE * 2)?;
-        clear_ctx.set_fill_style(&JsValue::from("#000"));
-        clear_ctx.begin_path();
-        clear_ctx.arc(
-            DROP_SIZE as f64,
-            DROP_SIZE as f64,
-            DROP_SIZE as f64,
-            0.0,
-            PI * 2.0,
-        )?;
-        clear_ctx.fill();
-
-        self.clear_gfx = Some(clear);
-        Ok(())
-    }
-
-    fn draw_drop(&self, ctx: &CanvasRenderingContext2d, drop: RefMut<Drop>) {
-        if !self.drops_gfx.is_empty() {
-            let x = drop.x;
-            let y = drop.y;
-            let r = drop.r;
-            let spread_x = drop.spread_x;
-            let spread_y = drop.spread_y;
-            let (min_r, _max_r) = self.opts.r;
-
-            let scale_x = 1.0;
-            let scale_y = 1.5;
-            let mut d = max(0.0, min(1.0, ((r - min_r) / self.delta_r()) * 0.9));
-            d *= 1.0 / (((spread_x + spread_y) * 0.5) + 1.0);
-            let d = (d * (self.drops_gfx.len() - 1) as f64).floor();
-
-            ctx.set_global_alpha(1.0);
-            // 新图像会覆盖在原有图像
-            ctx.set_global_composite_operation("source-over").unwrap();
-            ctx.draw_image_with_html_canvas_element_and_dw_and_dh(
-                &self.drops_gfx[d as usize],
-                (x - (r * scale_x * (spread_x + 1.0))) * self.scale,
-                (y - (r * scale_y * (spread_y + 1.0))) * self.scale,
-                (r * 2.0 * scale_x * (spread_x + 1.0)) * self.scale,
-                (r * 2.0 * scale_y * (spread_y + 1.0)) * self.scale,
-            )
-            .unwrap();
+    /// 水面纹理
+    pub fn water_map(&self) -> Ref<'_, M> {
+        self.water_map.borrow()
+    }
+
+    /// 画布大小或像素密度变化时调整纹理大小
+    ///
+    /// w / h 为新的纹理大小（像素），scale 为新的像素密度。已有的雨滴和雨迹按比例移到新的位置，
+    /// 雨滴层的内容拉伸到新的大小。
+    ///
+    /// Example:
+    /// ```rust
+    /// rain_drops.resize(canvas.width() as f64, canvas.height() as f64, dpi);
+    /// ```
+    pub fn resize(&mut self, w: f64, h: f64, scale: f64) {
+        let (old_w, old_h) = (self.width / self.scale, self.height / self.scale);
+        let (new_w, new_h) = (w / scale, h / scale);
+        if old_w > 0.0 && old_h > 0.0 {
+            let (sx, sy) = (new_w / old_w, new_h / old_h);
+            for drop in &self.drops {
+                let mut drop = drop.borrow_mut();
+                drop.x *= sx;
+                drop.y *= sy;
+            }
+            for trail in &self.trails {
+                for point in trail.borrow_mut().points.iter_mut() {
+                    point.x *= sx;
+                    point.y *= sy;
+                }
+            }
+        }
+
+        self.width = w;
+        self.height = h;
+        self.scale = scale;
+        self.water_map.borrow_mut().resize(w as u32, h as u32);
+        self.droplets.borrow_mut().resize(
+            (w * self.droplets_pixel_density) as u32,
+            (h * self.droplets_pixel_density) as u32,
+        );
+    }
+
+    /// 雨滴厚度（0.0 ~ 1.0），扩散时变薄
+    fn depth(&self, r: f64, spread_x: f64, spread_y: f64) -> f64 {
+        let (min_r, _max_r) = self.opts.r;
+        let d = (((r - min_r) / self.delta_r()) * 0.9).clamp(0.0, 1.0);
+        d / (((spread_x + spread_y) * 0.5) + 1.0)
+    }
+
+    fn draw_drop(&self, water_map: &mut M, drop: RefMut<Drop>) {
+        let x = drop.x;
+        let y = drop.y;
+        let r = drop.r;
+        let spread_x = drop.spread_x;
+        let spread_y = drop.spread_y;
+
+        let scale_x = 1.0;
+        let scale_y = 1.5;
+        let d = self.depth(r, spread_x, spread_y);
+
+        water_map.draw_drop(
+            d,
+            (x - (r * scale_x * (spread_x + 1.0))) * self.scale,
+            (y - (r * scale_y * (spread_y + 1.0))) * self.scale,
+            (r * 2.0 * scale_x * (spread_x + 1.0)) * self.scale,
+            (r * 2.0 * scale_y * (spread_y + 1.0)) * self.scale,
+        );
+    }
+
+    /// 绘制连续水痕：折射为零的细线，随时间变细
+    fn draw_trail(&self, water_map: &mut M, trail: &Trail) {
+        let bead_age = self.opts.trail_bead_age;
+        for segment in trail.points.windows(2) {
+            let (a, b) = (&segment[0], &segment[1]);
+            let r = b.r * Trail::narrowing(b, bead_age);
+            let d = (self.depth(r, 0.0, 0.0) * 255.0).floor();
+
+            water_map.draw_streak(
+                (a.x * self.scale, a.y * self.scale),
+                (b.x * self.scale, b.y * self.scale),
+                r * 2.0 * self.scale,
+                d as u8,
+            );
         }
     }
 
@@ -281,7 +399,7 @@ impl RainDrops {
         drop.borrow_mut().x = x * self.droplets_pixel_density;
         drop.borrow_mut().y = y * self.droplets_pixel_density;
         drop.borrow_mut().r = r * self.droplets_pixel_density;
-        self.draw_drop(&self.droplets.ctx, drop.borrow_mut());
+        self.draw_drop(&mut self.droplets.borrow_mut(), drop.borrow_mut());
     }
 
     fn clear_droplets(&self, x: f64, y: f64, r: Option<f64>) {
@@ -290,28 +408,17 @@ impl RainDrops {
             None => 40.0,
         };
         let multiplier = self.droplets_pixel_density * self.scale;
-        self.droplets
-            .ctx
-            .set_global_composite_operation("destination-out")
-            .unwrap();
-        self.droplets
-            .ctx
-            .draw_image_with_html_canvas_element_and_dw_and_dh(
-                self.clear_gfx.as_ref().unwrap(),
-                (x - r) * multiplier,
-                (y - r) * multiplier,
-                (r * 2.0) * multiplier,
-                (r * 2.0) * multiplier * 1.5,
-            )
-            .unwrap();
+        self.droplets.borrow_mut().erase(
+            (x - r) * multiplier,
+            (y - r) * multiplier,
+            (r * 2.0) * multiplier,
+            (r * 2.0) * multiplier * 1.5,
+        );
     }
 
     /// 清理画布
     fn clear_canvas(&self) {
-        self.texture
-            .borrow_mut()
-            .ctx
-            .clear_rect(0.0, 0.0, self.width, self.height);
+        self.water_map.borrow_mut().clear();
     }
 
     fn clear_texture(&mut self) {
@@ -319,12 +426,12 @@ impl RainDrops {
     }
 
     pub fn draw(&mut self) {
-        // 初期化画布
-        self.clear_canvas();
-
         // 当前计数(毫秒)
         let now = now();
-        let delta = now - self.last_time;
+        let delta = match self.last_time {
+            Some(last_time) => now - last_time,
+            None => 1000.0 / 60.0,
+        };
         // time_scale = delta时间内运行了帧动画
         // 限制刷新频率 60FPS(60/s)
         // time_scale = delta / ((1 / 60) * 1000)
@@ -332,10 +439,49 @@ impl RainDrops {
         if time_scale > 1.1 {
             time_scale = 1.1;
         }
-        time_scale *= self.opts.time_scale_multiplier;
-        self.last_time = now;
+        self.last_time = Some(now);
+
+        self.step(time_scale);
+    }
+
+    /// 按给定时标推进一帧（1.0 为 60FPS 下的一帧），不依赖系统时间
+    pub fn step(&mut self, time_scale: f64) {
+        // 初期化画布
+        self.clear_canvas();
+
+        self.update_drops(time_scale * self.opts.time_scale_multiplier);
+    }
+
+    /// 预热：尽快推进 seconds 秒的模拟时间，只把最后一帧描画到纹理
+    ///
+    /// Example:
+    /// ```rust
+    /// rain_drops.warm_up(20.0);
+    /// ```
+    pub fn warm_up(&mut self, seconds: f64) {
+        let frames = (seconds.max(0.0) * 60.0).ceil() as u32;
+        if frames == 0 {
+            return;
+        }
+
+        self.rendering = false;
+        for _ in 1..frames {
+            self.update_drops(1.0);
+        }
+        self.rendering = true;
+
+        self.clear_canvas();
+        self.update_drops(1.0);
+        self.reset_clock();
+    }
+
+    /// 重置计时，暂停恢复后不把暂停的时间算作一帧
+    pub fn reset_clock(&mut self) {
+        self.last_time = None;
+    }
 
-        self.update_drops(time_scale);
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        self.opts.time_scale_multiplier = time_scale.max(0.0);
     }
 
     /// 更新雨滴
@@ -344,76 +490,70 @@ impl RainDrops {
         if self.cleaning_iterations > 0.0 {
             self.cleaning_iterations -= time_scan;
 
-            // 绘制原图和新图不重叠部分
-            self.droplets
-                .ctx
-                .set_global_composite_operation("destination-out")
-                .unwrap();
-
-            // 半透明黑色
-            self.droplets
-                .ctx
-                .set_fill_style(&JsValue::from(format!("rgba(0,0,0,{})", 0.05 * time_scan)));
-            self.droplets.ctx.fill_rect(
-                0.0,
-                0.0,
-                self.width * self.droplets_pixel_density,
-                self.height * self.droplets_pixel_density,
-            );
+            self.droplets.borrow_mut().fade(0.05 * time_scan);
         }
 
         if self.opts.raining {
-            // 根据 分辨率+时间尺度+面积系数 计算累积雨滴数量
+            // 根据 分辨率+时间尺度+面积系数+阵风 计算累积雨滴数量
             self.droplets_counter +=
-                (self.opts.droplets_rate * time_scan * self.area_multiplier()) as u32;
-            let mut rng = thread_rng();
+                (self.opts.droplets_rate * time_scan * self.area_multiplier() * self.gust_level())
+                    as u32;
+            let mut rng = self.rng.borrow_mut();
             let (min, max) = self.opts.droplets_size;
             let (w, h) = (
                 (self.width / self.scale) as i32,
                 (self.height / self.scale) as i32,
             );
             while self.droplets_counter > 0 {
-                let x = rng.gen_range(0..w) as f64;
-                let y = rng.gen_range(0..h) as f64;
-                // 更多的小雨滴
-                let r = min + rng.gen::<f64>().powi(2) * (max - min);
-                self.draw_droplet(x, y, r);
+                let (spawn, gust) = self.gust_spawn(&mut *rng, 4, |rng| Spawn {
+                    x: rng.gen_range(0..w) as f64,
+                    y: rng.gen_range(0..h) as f64,
+                    // 更多的小雨滴
+                    size: rng.gen::<f64>().powi(2),
+                });
+                // 阵风中雨滴更大
+                let n = (spawn.size * (0.5 + 0.5 * gust)).min(1.0);
+                let r = min + n * (max - min);
+                self.draw_droplet(spawn.x, spawn.y, r);
                 self.droplets_counter -= 1;
             }
         }
 
-        self.texture
-            .borrow_mut()
-            .ctx
-            .draw_image_with_html_canvas_element_and_dw_and_dh(
-                &self.droplets.canvas,
-                0.0,
-                0.0,
-                self.width,
-                self.height,
-            )
-            .unwrap();
+        if self.rendering {
+            self.water_map
+                .borrow_mut()
+                .draw_layer(&self.droplets.borrow());
+        }
     }
 
     fn gen_drops(&self, time_scan: f64) -> Vec<Rc<RefCell<Drop>>> {
         let mut drops: Vec<Rc<RefCell<Drop>>> = Vec::new();
         if self.opts.raining {
+            // 阵风
+            let level = self.gust_level();
             // 雨量限制
-            let limit = (self.opts.rain_limit * time_scan * self.area_multiplier()) as i32;
+            let limit = (self.opts.rain_limit * time_scan * self.area_multiplier() * level) as i32;
             // 下雨几率
-            let chance = self.opts.rain_chance * time_scan * self.area_multiplier();
+            let chance = self.opts.rain_chance * time_scan * self.area_multiplier() * level;
 
             let mut count = 0;
-            let mut rng = thread_rng();
+            let mut rng = self.rng.borrow_mut();
             let (min, max) = self.opts.r;
             let (w, h) = (self.width / self.scale, self.height / self.scale);
             // 雨点在Y轴生成范围
             let [spawn_min, spawn_max] = self.opts.spawn_area.map(|x| x * h);
+            let area = SpawnArea {
+                width: w,
+                height: h,
+                y_range: (spawn_min, spawn_max),
+            };
+            let strategy = self.opts.spawn.clone();
             while rng.gen::<f64>() <= chance && count < limit {
                 count += 1;
-                let x = rng.gen_range(0..w as i32) as f64;
-                let y = rng.gen_range(spawn_min as i32..spawn_max as i32) as f64;
-                let n = rng.gen::<f64>().powi(3);
+                let (spawn, gust) =
+                    self.gust_spawn(&mut *rng, 8, |rng| strategy.borrow_mut().spawn(&area, rng));
+                let (x, y) = (spawn.x, spawn.y);
+                let n = (spawn.size * (0.5 + 0.5 * gust)).min(1.0);
                 let r = min + n * (max - min);
                 let momentum = 1.0 + (r - min) * 0.1 + rng.gen::<f64>() * 2.0;
 
@@ -435,8 +575,50 @@ impl RainDrops {
         drops
     }
 
+    /// 整体阵风强度，随时间起伏形成一阵一阵的雨（平均约为 1.0）
+    fn gust_level(&self) -> f64 {
+        if self.opts.gust_strength <= 0.0 {
+            return 1.0;
+        }
+        let t = self.elapsed * self.opts.gust_speed * 0.25;
+        let n = self.noise.fbm(t, 0.0, 17.0, 2);
+        (1.0 + n * 2.0 * self.opts.gust_strength).max(0.0)
+    }
+
+    /// 局部阵风强度，噪声场随时间横向移动，形成扫过玻璃的雨带
+    fn gust_at(&self, x: f64, y: f64) -> f64 {
+        if self.opts.gust_strength <= 0.0 {
+            return 1.0;
+        }
+        let t = self.elapsed * self.opts.gust_speed;
+        let scale = self.opts.gust_scale;
+        let n = self.noise.fbm(x / scale - t, y / scale, t * 0.5, 3);
+        (1.0 + n * 2.0 * self.opts.gust_strength).max(0.0)
+    }
+
+    /// 按局部阵风强度在候选位置中取舍，返回 (雨点, 阵风强度)
+    fn gust_spawn<R: RngCore>(
+        &self,
+        rng: &mut R,
+        tries: u32,
+        mut next: impl FnMut(&mut dyn RngCore) -> Spawn,
+    ) -> (Spawn, f64) {
+        let peak = 1.0 + 2.0 * self.opts.gust_strength;
+        let mut spawn = next(rng);
+        let mut gust = self.gust_at(spawn.x, spawn.y);
+        for _ in 1..tries {
+            if rng.gen::<f64>() * peak < gust {
+                break;
+            }
+            spawn = next(rng);
+            gust = self.gust_at(spawn.x, spawn.y);
+        }
+        (spawn, gust)
+    }
+
     // 更新雨滴下落过程
     fn update_drops(&mut self, time_scan: f64) {
+        self.elapsed += time_scan;
         self.update_droplets(time_scan);
         let mut drops = self.gen_drops(time_scan);
 
@@ -449,11 +631,16 @@ impl RainDrops {
         });
 
         let (min_r, max_r) = self.opts.r;
-        let mut rng = thread_rng();
 
         let drop_fall = min_r * self.opts.drop_fall_multiplier;
         let delta_r = 0.1 / self.delta_r() * time_scan;
         let is_full_drops = self.is_full_drops();
+
+        // 连续雨迹画在雨滴下层
+        let streaks = self.opts.trail_mode == TrailMode::Streaks;
+        self.update_trails(time_scan, &mut drops);
+        let mut rng = self.rng.borrow_mut();
+        let mut new_trails: Vec<Rc<RefCell<Trail>>> = Vec::new();
         for (i, rc_drop) in self.drops.iter().enumerate() {
             let mut drop = rc_drop.borrow_mut();
             if !drop.killed {
@@ -478,18 +665,21 @@ impl RainDrops {
                 if self.opts.raining {
                     drop.last_spawn += drop.momentum * time_scan * self.opts.trail_rate;
                     if drop.last_spawn > drop.next_spawn {
-                        if !is_full_drops {
-                            let new_drop = Drop::new();
-                            new_drop.borrow_mut().x =
-                                drop.x + (-drop.r + rng.gen::<f64>() * 2.0 * drop.r) * 0.1;
-                            new_drop.borrow_mut().y = drop.y - drop.r * 0.01;
-                            let [trail_min, trail_max] = self.opts.trail_scale_range;
-                            new_drop.borrow_mut().r =
-                                drop.r * (trail_min + rng.gen::<f64>() * (trail_max - trail_min));
-                            new_drop.borrow_mut().spread_y = drop.momentum * 0.1;
-                            new_drop.borrow_mut().parent = Some(Rc::clone(rc_drop));
-
-                            drops.push(Rc::clone(&new_drop));
+                        // 连续雨迹不生成小雨滴，只损失水量
+                        if streaks || !is_full_drops {
+                            if !streaks {
+                                let new_drop = Drop::new();
+                                new_drop.borrow_mut().x =
+                                    drop.x + (-drop.r + rng.gen::<f64>() * 2.0 * drop.r) * 0.1;
+                                new_drop.borrow_mut().y = drop.y - drop.r * 0.01;
+                                let [trail_min, trail_max] = self.opts.trail_scale_range;
+                                new_drop.borrow_mut().r = drop.r
+                                    * (trail_min + rng.gen::<f64>() * (trail_max - trail_min));
+                                new_drop.borrow_mut().spread_y = drop.momentum * 0.1;
+                                new_drop.borrow_mut().parent = Some(Rc::clone(rc_drop));
+
+                                drops.push(Rc::clone(&new_drop));
+                            }
 
                             drop.r *= 0.97_f32.powf(time_scan as f32) as f64;
                             drop.last_spawn = 0.0;
@@ -514,6 +704,24 @@ impl RainDrops {
                     }
                 }
 
+                // 延长连续雨迹
+                if streaks && moved && !drop.killed {
+                    let [trail_min, trail_max] = self.opts.trail_scale_range;
+                    let r = drop.r * (trail_min + trail_max) * 0.5;
+                    let (x, y) = (drop.x, drop.y - drop.r * 0.5);
+                    match &drop.trail {
+                        Some(trail) if !trail.borrow().is_closed() => {
+                            trail.borrow_mut().push(x, y, r);
+                        }
+                        _ => {
+                            let trail = Rc::new(RefCell::new(Trail::new(rc_drop)));
+                            trail.borrow_mut().push(x, y, r);
+                            new_trails.push(Rc::clone(&trail));
+                            drop.trail = Some(trail);
+                        }
+                    }
+                }
+
                 // 碰撞
                 let collision = (moved || drop.is_new) && !drop.killed;
                 drop.is_new = false;
@@ -554,14 +762,11 @@ impl RainDrops {
                                     drop.momentum_x += dx * 0.1;
                                     drop.spread_x = 0.0;
                                     drop.spread_y = 0.0;
-                                    drop.momentum = max(
-                                        drop2.momentum,
-                                        min(
-                                            40.0,
-                                            drop.momentum
-                                                + r * self.opts.collision_boost_multiplier
-                                                + self.opts.collision_boost,
-                                        ),
+                                    drop.momentum = drop2.momentum.max(
+                                        (drop.momentum
+                                            + r * self.opts.collision_boost_multiplier
+                                            + self.opts.collision_boost)
+                                            .min(40.0),
                                     );
                                     drop2.killed = true;
                                 }
@@ -571,7 +776,7 @@ impl RainDrops {
                 }
 
                 // 放慢流动速度
-                drop.momentum -= max(1.0, min_r * 0.5 - drop.momentum) * 0.1 * time_scan;
+                drop.momentum -= (min_r * 0.5 - drop.momentum).max(1.0) * 0.1 * time_scan;
                 if drop.momentum < 0.0 {
                     drop.momentum = 0.0;
                 }
@@ -585,12 +790,57 @@ impl RainDrops {
                         self.clear_droplets(drop.x, drop.y, Some(r));
                     }
 
-                    self.draw_drop(&self.texture.borrow_mut().ctx, drop);
+                    if self.rendering {
+                        self.draw_drop(&mut self.water_map.borrow_mut(), drop);
+                    }
                 }
             }
         }
 
         self.drops = drops;
+        self.trails.extend(new_trails);
+
+        self.droplets.borrow_mut().flush();
+        self.water_map.borrow_mut().flush();
+    }
+
+    /// 更新连续雨迹：变细、断裂成水珠，并绘制到纹理
+    fn update_trails(&mut self, time_scan: f64, drops: &mut Vec<Rc<RefCell<Drop>>>) {
+        let bead_age = self.opts.trail_bead_age;
+        let mut rng = self.rng.borrow_mut();
+        for trail in self.trails.iter() {
+            let mut trail = trail.borrow_mut();
+            trail.update(time_scan);
+
+            let beads = trail.take_beads(bead_age, &mut *rng);
+            let parent = trail.owner();
+            for (x, y, r) in beads {
+                if self.is_full_drops() {
+                    break;
+                }
+                let bead = Drop::new();
+                bead.borrow_mut().x = x;
+                bead.borrow_mut().y = y;
+                bead.borrow_mut().r = r;
+                bead.borrow_mut().parent = parent.clone();
+                drops.push(bead);
+            }
+        }
+
+        self.trails.retain(|trail| {
+            let finished = trail.borrow().is_finished(bead_age);
+            if finished {
+                trail.borrow_mut().close();
+            }
+            !finished
+        });
+
+        if self.rendering {
+            let mut water_map = self.water_map.borrow_mut();
+            for trail in self.trails.iter() {
+                self.draw_trail(&mut water_map, &trail.borrow());
+            }
+        }
     }
 
     fn is_full_drops(&self) -> bool {
@@ -617,6 +867,10 @@ impl RainDrops {
         max - min
     }
 
+    pub fn set_trail_mode(&mut self, mode: TrailMode) {
+        self.opts.trail_mode = mode;
+    }
+
     pub fn set_options(&mut self, opts: &WeatherOptions) {
         self.opts.raining = opts.raining;
         self.opts.r = opts.r;
@@ -627,5 +881,9 @@ impl RainDrops {
         self.opts.trail_rate = opts.trail_rate;
         self.opts.trail_scale_range = opts.trail_scale_range;
         self.opts.collision_radius_increase = opts.collision_radius_increase;
+        self.opts.gust_strength = opts.gust_strength;
+        if let Some(spawn) = &opts.spawn {
+            self.opts.spawn = spawn.clone();
+        }
     }
 }
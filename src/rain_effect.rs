@@ -1,32 +1,79 @@
+use crate::animation::AnimationLoop;
+use crate::blur::{gaussian_blur, BlurOptions};
+use crate::context_loss::ContextLossListener;
+use crate::image_future::ImageFuture;
 use crate::images::{Images, WeatherImage};
+use crate::lightning::Lightning;
+use crate::parallax::{Parallax, ParallaxSource};
+use crate::parallax_input::ParallaxListener;
+use crate::post_process::PostEffect;
 use crate::rain_drops::{RainDrops, RainDropsOptions};
 use crate::rain_render::{RainRender, RainRenderOptions};
+use crate::resize::ResizeListener;
+use crate::rgba::RgbaImage;
+use crate::safety::{FlashLimiter, SafetyOptions};
+use crate::shine::{parse_color, Light};
+use crate::spawn::{
+    ClusteredSpawn, DensitySpawn, EdgeSpawn, FunctionSpawn, SpawnStrategy, UniformSpawn,
+};
 use crate::textures::{BgSize, FgSize, Texture};
+use crate::trail::TrailMode;
+use crate::transition::Transition;
+use crate::video::VideoSource;
 use crate::weather::Weather;
-use crate::{create_canvas_element, document, now, request_animation_frame};
-use js_sys::Map;
-use std::cell::RefCell;
+use crate::{create_canvas_element, document, now};
+use js_sys::{Function, Map, Promise};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
 use wasm_bindgen::JsCast;
-use web_sys::{console, window, HtmlCanvasElement};
+use wasm_bindgen_futures::future_to_promise;
+use web_sys::{
+    console, window, CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, ImageData,
+    MediaStream,
+};
 
 #[wasm_bindgen]
 pub struct RainEffect {
-    dpi: f64,
+    // 当前的 devicePixelRatio
+    dpi: Rc<Cell<f64>>,
     canvas: Rc<RefCell<HtmlCanvasElement>>,
     fg: Rc<RefCell<Texture>>,
     bg: Rc<RefCell<Texture>>,
     weather_data: Rc<RefCell<Weather>>,
     rain_drops: Rc<RefCell<RainDrops>>,
+    // 双层玻璃的后层
+    back_pane: Rc<RefCell<Option<RainDrops>>>,
     rain_render: Rc<RefCell<RainRender>>,
+    images: Rc<Images>,
+    // 背景纹理的模糊
+    blur: Rc<Cell<BlurOptions>>,
+    transition: Rc<RefCell<Option<Transition>>>,
+    lightning: Rc<RefCell<Lightning>>,
+    // 代替天气图片的视频
+    video: Rc<RefCell<Option<VideoSource>>>,
+    // 由鼠标、设备倾斜和页面滚动合成的视差
+    parallax: Rc<RefCell<Parallax>>,
+    parallax_listener: RefCell<Option<ParallaxListener>>,
+    // 光敏性安全限制，None 为不限制
+    safety: Rc<RefCell<Option<FlashLimiter>>>,
+    // 动画循环
+    animation: Rc<RefCell<Option<AnimationLoop>>>,
+    // 暂停的时间
+    paused_at: Rc<Cell<Option<f64>>>,
+    // WebGL 上下文恢复后是否继续动画
+    resume_on_restore: Rc<Cell<bool>>,
+    context_loss: RefCell<Option<ContextLossListener>>,
+    resize_listener: RefCell<Option<ResizeListener>>,
+    destroyed: Cell<bool>,
 }
 
 #[wasm_bindgen]
 impl RainEffect {
     #[wasm_bindgen(constructor)]
-    pub async fn new(id: String, map: Map) -> Self {
+    pub async fn new(id: String, map: Map) -> Result<RainEffect, JsValue> {
         if id.is_empty() {
             panic!("canvas id is empty!")
         }
@@ -45,133 +92,964 @@ impl RainEffect {
         );
         canvas.set_width((w * dpi) as u32);
         canvas.set_height((h * dpi) as u32);
-        canvas
-            .style()
-            .set_property("width", &format!("{}px", w))
-            .unwrap();
-        canvas
-            .style()
-            .set_property("height", &format!("{}px", h))
-            .unwrap();
+        // 随窗口大小变化，由 ResizeObserver 调整画布
+        canvas.style().set_property("width", "100vw").unwrap();
+        canvas.style().set_property("height", "100vh").unwrap();
 
         let values: HashMap<String, String> = map.into_serde().unwrap();
-        let images = Images::new(values).await;
-        let (fg, bg) = RainEffect::create_textures(images.weather.clone());
+        let images = Rc::new(Images::new(values).await);
+        let blur = BlurOptions::new();
+        let (fg, bg) = RainEffect::create_textures(images.weather.clone(), blur);
         let (fg, bg) = (Rc::new(RefCell::new(fg)), Rc::new(RefCell::new(bg)));
 
-        let weather_data = Weather::new_with_img(images.weather.clone());
+        let mut weather_data = Weather::new_with_img(images.weather.clone());
+        let name = images.weather.borrow().to_string();
+        weather_data.options_mut().flash = RainEffect::load_flash(&images, &name).await;
 
-        let mut opts = RainDropsOptions::new();
-        opts.trail_rate = 1.0;
-        opts.trail_scale_range = [0.2, 0.45];
-        opts.collision_radius = 0.45;
-        opts.droplets_cleaning_radius_multiplier = 0.28;
         let (w, h) = (canvas.width() as f64, canvas.height() as f64);
+        let canvas = Rc::new(RefCell::new(canvas));
 
-        let mut rain_drops =
-            RainDrops::new(w * dpi, h * dpi, dpi, Rc::clone(&images.drop), Some(opts));
-        rain_drops.render_droplets().unwrap();
-        rain_drops.set_options(weather_data.options());
+        let opts = RainRenderOptions::window();
 
-        let drops_texture = rain_drops.texture.clone();
+        let mut rain_render =
+            RainRender::new(Rc::clone(&canvas), fg.clone(), bg.clone(), Some(opts))
+                .map_err(JsValue::from)?;
 
-        let rain_drops = Rc::new(RefCell::new(rain_drops));
+        let opts = RainDropsOptions::window();
 
-        let canvas = Rc::new(RefCell::new(canvas));
-
-        let mut opts = RainRenderOptions::new();
-        opts.brightness = 1.04;
-        opts.alpha_multiply = 6.0;
-        opts.alpha_subtract = 3.0;
-
-        let rain_render = RainRender::new(
-            Rc::clone(&canvas),
-            drops_texture,
-            fg.clone(),
-            bg.clone(),
+        // WebGL2 下水面纹理直接在渲染上下文中描画
+        let mut rain_drops = RainDrops::new(
+            w,
+            h,
+            dpi,
+            Rc::clone(&images.drop),
+            rain_render.webgl2().as_ref(),
             Some(opts),
         );
+        rain_drops.render_droplets().unwrap();
+        rain_drops.set_options(weather_data.options());
+        rain_render.set_water_map(rain_drops.source());
+        rain_render.set_flash_textures(RainEffect::flash_textures(&weather_data, blur));
+
+        let rain_drops = Rc::new(RefCell::new(rain_drops));
 
         let rain_render = Rc::new(RefCell::new(rain_render));
         let weather_data = Rc::new(RefCell::new(weather_data));
 
-        RainEffect {
-            dpi,
+        let effect = RainEffect {
+            dpi: Rc::new(Cell::new(dpi)),
             canvas,
             fg,
             bg,
             rain_drops,
+            back_pane: Rc::new(RefCell::new(None)),
             rain_render,
             weather_data,
+            images,
+            blur: Rc::new(Cell::new(blur)),
+            transition: Rc::new(RefCell::new(None)),
+            lightning: Rc::new(RefCell::new(Lightning::new(None))),
+            safety: Rc::new(RefCell::new(None)),
+            video: Rc::new(RefCell::new(None)),
+            parallax: Rc::new(RefCell::new(Parallax::new())),
+            parallax_listener: RefCell::new(None),
+            animation: Rc::new(RefCell::new(None)),
+            paused_at: Rc::new(Cell::new(None)),
+            resume_on_restore: Rc::new(Cell::new(false)),
+            context_loss: RefCell::new(None),
+            resize_listener: RefCell::new(None),
+            destroyed: Cell::new(false),
+        };
+        if effect.rain_render.borrow().webgl_version() > 0 {
+            *effect.context_loss.borrow_mut() = Some(effect.listen_context_loss());
+        }
+        *effect.resize_listener.borrow_mut() = Some(effect.listen_resize());
+        if RainEffect::prefers_reduced_motion() {
+            effect.set_photosensitivity_limit(true);
+        }
+        Ok(effect)
+    }
+
+    fn prefers_reduced_motion() -> bool {
+        window()
+            .unwrap()
+            .match_media("(prefers-reduced-motion: reduce)")
+            .ok()
+            .flatten()
+            .is_some_and(|query| query.matches())
+    }
+
+    fn create_textures(
+        weather: Rc<RefCell<WeatherImage>>,
+        blur: BlurOptions,
+    ) -> (Texture, Texture) {
+        let (fg, fg_ctx) =
+            create_canvas_element(FgSize::Width as u32, FgSize::Height as u32).unwrap();
+        let (bg, bg_ctx) =
+            create_canvas_element(BgSize::Width as u32, BgSize::Height as u32).unwrap();
+        let fg = Texture {
+            canvas: fg,
+            ctx: fg_ctx,
+        };
+        let bg = Texture {
+            canvas: bg,
+            ctx: bg_ctx,
+        };
+        RainEffect::draw_weather(&fg, &bg, &weather.borrow(), blur);
+
+        (fg, bg)
+    }
+
+    // 把天气图片画到前景和背景纹理上
+    fn draw_weather(fg: &Texture, bg: &Texture, weather: &WeatherImage, blur: BlurOptions) {
+        let (image_fg, image_bg) = RainEffect::weather_images(weather);
+        let (w, h) = (FgSize::Width as u32 as f64, FgSize::Height as u32 as f64);
+        fg.ctx.clear_rect(0.0, 0.0, w, h);
+        RainEffect::image_drawer(image_fg)(&fg.ctx, w, h);
+        RainEffect::draw_background(bg, RainEffect::image_drawer(image_bg), blur);
+    }
+
+    // 把图片画满 (0, 0, w, h)
+    fn image_drawer(image: &HtmlImageElement) -> impl Fn(&CanvasRenderingContext2d, f64, f64) + '_ {
+        move |ctx, w, h| {
+            ctx.draw_image_with_html_image_element_and_dw_and_dh(image, 0.0, 0.0, w, h)
+                .unwrap();
         }
     }
 
-    fn create_textures(weather: Rc<RefCell<WeatherImage>>) -> (Texture, Texture) {
-        let weather = weather.borrow();
-        let (image_fg, image_bg) = match &*weather {
+    async fn load_flash(images: &Images, name: &str) -> Option<Rc<RefCell<WeatherImage>>> {
+        let image = images.load_flash(name).await?;
+        Some(Rc::new(RefCell::new(image)))
+    }
+
+    // 当前天气的闪电纹理
+    fn flash_textures(weather_data: &Weather, blur: BlurOptions) -> Option<(Texture, Texture)> {
+        let flash = weather_data.options().flash.clone()?;
+        Some(RainEffect::create_textures(flash, blur))
+    }
+
+    fn weather_images(weather: &WeatherImage) -> (&HtmlImageElement, &HtmlImageElement) {
+        match weather {
             WeatherImage::Rain(image)
             | WeatherImage::Fallout(image)
             | WeatherImage::Storm(image)
             | WeatherImage::Sun(image)
             | WeatherImage::Drizzle(image) => (&image.fg, &image.bg),
-        };
-        let alpha = 1.0;
-        let (fg, fg_ctx) =
-            create_canvas_element(FgSize::Width as u32, FgSize::Height as u32).unwrap();
-        fg_ctx.set_global_alpha(alpha);
-
-        fg_ctx
-            .draw_image_with_html_image_element_and_dw_and_dh(
-                &image_fg,
-                0.0,
-                0.0,
-                FgSize::Width as u32 as f64,
-                FgSize::Height as u32 as f64,
-            )
-            .unwrap();
+        }
+    }
 
-        let (bg, bg_ctx) =
-            create_canvas_element(BgSize::Width as u32, BgSize::Height as u32).unwrap();
-        bg_ctx.set_global_alpha(alpha);
-
-        bg_ctx
-            .draw_image_with_html_image_element_and_dw_and_dh(
-                &image_bg,
-                0.0,
-                0.0,
-                FgSize::Width as u32 as f64,
-                FgSize::Height as u32 as f64,
-            )
-            .unwrap();
+    /// 用 draw 把背景画到背景纹理上，按 blur 做可分离的高斯模糊
+    ///
+    /// draw 画满 (0, 0, w, h)。只模糊透过干燥玻璃看到的背景，雨滴里折射的前景纹理保持清晰。
+    fn draw_background(
+        bg: &Texture,
+        draw: impl Fn(&CanvasRenderingContext2d, f64, f64),
+        blur: BlurOptions,
+    ) {
+        let (w, h) = (BgSize::Width as u32 as f64, BgSize::Height as u32 as f64);
+        bg.ctx.clear_rect(0.0, 0.0, w, h);
+        if blur.radius <= 0.0 {
+            draw(&bg.ctx, w, h);
+            return;
+        }
 
-        (
-            Texture {
-                canvas: fg,
-                ctx: fg_ctx,
-            },
-            Texture {
-                canvas: bg,
-                ctx: bg_ctx,
-            },
-        )
+        // 在缩小的画布上模糊，再拉伸回背景纹理
+        let scale = blur.quality.clamp(0.1, 1.0);
+        let (sw, sh) = ((w * scale).ceil() as u32, (h * scale).ceil() as u32);
+        let (small, ctx) = create_canvas_element(sw, sh).unwrap();
+        draw(&ctx, sw as f64, sh as f64);
+        let data = ctx
+            .get_image_data(0.0, 0.0, sw as f64, sh as f64)
+            .unwrap()
+            .data();
+        let mut pixels = RgbaImage::from_raw(sw, sh, data.0).unwrap();
+        gaussian_blur(&mut pixels, blur.radius * scale);
+        let data =
+            ImageData::new_with_u8_clamped_array_and_sh(Clamped(&pixels.data), sw, sh).unwrap();
+        ctx.put_image_data(&data, 0.0, 0.0).unwrap();
+
+        bg.ctx
+            .draw_image_with_html_canvas_element_and_dw_and_dh(&small, 0.0, 0.0, w, h)
+            .unwrap();
     }
 
     pub fn draw(&self) {
-        let f = Rc::new(RefCell::new(None));
-        let g = f.clone();
+        if self.destroyed.get() {
+            return;
+        }
+
+        self.rain_render.borrow().update_textures();
+
+        let mut animation = self.animation.borrow_mut();
+        let animation = animation.get_or_insert_with(|| {
+            let rain_drops = self.rain_drops.clone();
+            let rain_render = self.rain_render.clone();
+            let back_pane = self.back_pane.clone();
+            let images = self.images.clone();
+            let weather_data = self.weather_data.clone();
+            let transition = self.transition.clone();
+            let blur = self.blur.clone();
+            let lightning = self.lightning.clone();
+            let safety = self.safety.clone();
+            let parallax = self.parallax.clone();
+            let video = self.video.clone();
+            let fg = self.fg.clone();
+            let bg = self.bg.clone();
+
+            AnimationLoop::new(move || {
+                RainEffect::update_transition(
+                    &transition,
+                    &images,
+                    &rain_drops,
+                    &rain_render,
+                    &weather_data,
+                    blur.get(),
+                );
+                RainEffect::update_lightning(
+                    &lightning,
+                    &safety,
+                    &transition,
+                    &weather_data,
+                    &rain_render,
+                );
+                RainEffect::update_video(&video, &fg, &bg, &transition, &rain_render, blur.get());
+                let (x, y) = parallax.borrow_mut().update(now());
+                rain_render.borrow_mut().set_parallax(x, y);
+                rain_drops.borrow_mut().draw();
+                if let Some(back_pane) = back_pane.borrow_mut().as_mut() {
+                    back_pane.draw();
+                }
+                rain_render.borrow().draw();
+            })
+        });
+        // 上下文丢失时等恢复后再开始
+        if self.rain_render.borrow().is_context_lost() {
+            self.resume_on_restore.set(true);
+        } else {
+            animation.start();
+        }
+    }
+
+    /// 暂停动画
+    pub fn pause(&self) {
+        self.resume_on_restore.set(false);
+        if let Some(animation) = self.animation.borrow().as_ref() {
+            if animation.is_running() {
+                animation.stop();
+                self.paused_at.set(Some(now()));
+            }
+        }
+    }
+
+    /// 恢复动画，暂停的时间不计入模拟和天气过渡
+    pub fn resume(&self) {
+        if self.destroyed.get() {
+            return;
+        }
+        if self.rain_render.borrow().is_context_lost() {
+            self.resume_on_restore.set(true);
+            return;
+        }
+
+        RainEffect::resume_animation(
+            &self.paused_at,
+            &self.transition,
+            &self.rain_drops,
+            &self.back_pane,
+            &self.animation,
+        );
+    }
+
+    /// 逐帧推进 n 帧（每帧为 60FPS 下的一帧）并描画，通常在暂停时使用
+    pub fn step(&self, n: u32) {
+        if self.destroyed.get() {
+            return;
+        }
+
+        for _ in 0..n {
+            self.rain_drops.borrow_mut().step(1.0);
+            if let Some(back_pane) = self.back_pane.borrow_mut().as_mut() {
+                back_pane.step(1.0);
+            }
+        }
+        self.rain_render.borrow().draw();
+    }
+
+    /// 预热：尽快推进 seconds 秒的模拟时间，打开页面时玻璃已经是湿的
+    ///
+    /// Example:
+    /// ```javascript
+    /// effect.warm_up(20);
+    /// effect.draw();
+    /// ```
+    pub fn warm_up(&self, seconds: f64) {
+        if self.destroyed.get() {
+            return;
+        }
+
+        self.rain_drops.borrow_mut().warm_up(seconds);
+        if let Some(back_pane) = self.back_pane.borrow_mut().as_mut() {
+            back_pane.warm_up(seconds);
+        }
+    }
+
+    /// 设置模拟速度（1.0 为正常速度）
+    pub fn set_time_scale(&self, time_scale: f64) {
+        self.rain_drops.borrow_mut().set_time_scale(time_scale);
+        if let Some(back_pane) = self.back_pane.borrow_mut().as_mut() {
+            back_pane.set_time_scale(time_scale);
+        }
+    }
+
+    /// 停止动画，释放动画帧闭包和 WebGL 资源，之后不能再使用
+    pub fn destroy(&self) {
+        if self.destroyed.replace(true) {
+            return;
+        }
+
+        if let Some(animation) = self.animation.borrow_mut().take() {
+            animation.destroy();
+        }
+        self.context_loss.borrow_mut().take();
+        self.resize_listener.borrow_mut().take();
+        self.parallax_listener.borrow_mut().take();
+        self.video.borrow_mut().take();
+        self.transition.borrow_mut().take();
+        self.back_pane.borrow_mut().take();
+        self.rain_render.borrow_mut().destroy();
+    }
+
+    /// 把画布调整为 width x height（CSS 像素），画布像素按当前的 devicePixelRatio 计算
+    ///
+    /// 画布默认铺满窗口并自动跟随窗口大小，需要固定大小时使用。
+    ///
+    /// Example:
+    /// ```javascript
+    /// effect.resize(800, 600);
+    /// ```
+    pub fn resize(&self, width: f64, height: f64) {
+        if self.destroyed.get() {
+            return;
+        }
+        {
+            let style = self.canvas.borrow().style();
+            style
+                .set_property("width", &format!("{}px", width))
+                .unwrap();
+            style
+                .set_property("height", &format!("{}px", height))
+                .unwrap();
+        }
+        RainEffect::resize_canvas(
+            &self.canvas,
+            &self.rain_drops,
+            &self.back_pane,
+            &self.rain_render,
+            &self.dpi,
+            width,
+            height,
+        );
+    }
+
+    /// 使用的 WebGL 版本：2 为 WebGL2，不支持时回退为 1，没有 WebGL 时为 0（Canvas2D）
+    pub fn webgl_version(&self) -> u32 {
+        self.rain_render.borrow().webgl_version()
+    }
+
+    /// 是否支持浮点渲染目标
+    pub fn float_render_targets(&self) -> bool {
+        self.rain_render.borrow().float_render_targets()
+    }
+
+    /// 开启或关闭双层玻璃
+    ///
+    /// 后层玻璃有独立的雨滴模拟和水面纹理，透过前层玻璃的雨滴折射显示。
+    pub fn set_double_pane(&self, enabled: bool) {
+        let mut back_pane = self.back_pane.borrow_mut();
+        if !enabled {
+            *back_pane = None;
+            self.rain_render.borrow_mut().set_back_pane(None);
+            return;
+        }
+
+        if back_pane.is_none() {
+            let canvas = self.canvas.borrow();
+            let (w, h) = (canvas.width() as f64, canvas.height() as f64);
+            let mut rain_render = self.rain_render.borrow_mut();
+            let mut rain_drops = RainDrops::new(
+                w,
+                h,
+                self.dpi.get(),
+                Rc::clone(&self.images.drop),
+                rain_render.webgl2().as_ref(),
+                Some(RainDropsOptions::condensation()),
+            );
+            rain_drops.render_droplets().unwrap();
+
+            rain_render.set_back_pane(Some(rain_drops.source()));
+            *back_pane = Some(rain_drops);
+        }
+    }
+
+    /// 雨迹样式：drops（一串小雨滴）、streaks（连续水痕）
+    pub fn set_trail_mode(&self, name: String) {
+        let mode = match name.as_str() {
+            "streaks" => TrailMode::Streaks,
+            _ => TrailMode::Drops,
+        };
+        self.rain_drops.borrow_mut().set_trail_mode(mode);
+        if let Some(back_pane) = self.back_pane.borrow_mut().as_mut() {
+            back_pane.set_trail_mode(mode);
+        }
+    }
+
+    /// 选择内置的雨点生成策略：uniform、clustered、edge
+    pub fn set_spawn(&self, name: String) {
+        let spawn: Rc<RefCell<dyn SpawnStrategy>> = match name.as_str() {
+            "clustered" => Rc::new(RefCell::new(ClusteredSpawn::new())),
+            "edge" => Rc::new(RefCell::new(EdgeSpawn::new())),
+            _ => Rc::new(RefCell::new(UniformSpawn)),
+        };
+        RainEffect::apply_spawn(&self.weather_data, &self.rain_drops, spawn);
+    }
+
+    /// 按图片亮度分布生成雨点，图片加载完成后 Promise 完成
+    pub fn set_spawn_density(&self, src: String) -> Promise {
+        let weather_data = self.weather_data.clone();
         let rain_drops = self.rain_drops.clone();
+
+        future_to_promise(async move {
+            let image = ImageFuture::new(&src)
+                .await
+                .map_err(|_| JsValue::from(format!("failed to load {}", src)))?;
+            let spawn = DensitySpawn::from_image(&image, 128, 128)?;
+            RainEffect::apply_spawn(&weather_data, &rain_drops, Rc::new(RefCell::new(spawn)));
+
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// 由 JavaScript 函数生成雨点：(width, height) => [x, y, size]
+    pub fn set_spawn_fn(&self, f: Function) {
+        let spawn = Rc::new(RefCell::new(FunctionSpawn::new(f)));
+        RainEffect::apply_spawn(&self.weather_data, &self.rain_drops, spawn);
+    }
+
+    /// 设置后处理效果，按数组顺序作用在雨的合成结果上，空数组为关闭
+    ///
+    /// 需要 WebGL2，颜色查找表的图片加载完成后 Promise 完成。
+    ///
+    /// Example:
+    /// ```javascript
+    /// effect.set_post_effects([
+    ///     { type: "bloom", threshold: 0.7, intensity: 0.6 },
+    ///     { type: "color_grading", src: "lut.png" },
+    ///     { type: "vignette" },
+    ///     { type: "film_grain", amount: 0.05 },
+    /// ]);
+    /// ```
+    pub fn set_post_effects(&self, effects: JsValue) -> Promise {
         let rain_render = self.rain_render.clone();
 
-        rain_render.borrow().update_textures();
+        future_to_promise(async move {
+            let mut effects: Vec<PostEffect> = serde_wasm_bindgen::from_value(effects)
+                .map_err(|err| JsValue::from(format!("invalid post effects: {}", err)))?;
+            for effect in effects.iter_mut() {
+                if let PostEffect::ColorGrading(opts) = effect {
+                    let image = ImageFuture::new(&opts.src)
+                        .await
+                        .map_err(|_| JsValue::from(format!("failed to load {}", opts.src)))?;
+                    opts.lut = Some(image);
+                }
+            }
+            rain_render.borrow_mut().set_post_effects(effects);
+            rain_render.borrow().draw();
+
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// 设置透过干燥玻璃看到的背景的模糊，雨滴里折射的画面保持清晰
+    ///
+    /// radius 为背景纹理上的像素（384x256），0 为不模糊；
+    /// quality 为进行模糊的分辨率比例 (0.0, 1.0]，越小越快。
+    ///
+    /// Example:
+    /// ```javascript
+    /// effect.set_background_blur(8, 0.5);
+    /// ```
+    pub fn set_background_blur(&self, radius: f64, quality: f64) {
+        let blur = BlurOptions {
+            radius: radius.max(0.0),
+            // NaN 会让缩小后的画布尺寸变为 0
+            quality: if quality.is_finite() {
+                quality.clamp(0.1, 1.0)
+            } else {
+                1.0
+            },
+        };
+        if self.blur.replace(blur) == blur {
+            return;
+        }
+
+        match self.video.borrow().as_ref() {
+            // 视频的下一帧按新的模糊重画
+            Some(video) => video.redraw(),
+            None => {
+                let weather = self.images.weather.borrow();
+                let image = RainEffect::weather_images(&weather).1;
+                RainEffect::draw_background(
+                    &self.bg.borrow(),
+                    RainEffect::image_drawer(image),
+                    blur,
+                );
+            }
+        }
+        let mut rain_render = self.rain_render.borrow_mut();
+        if let Some(transition) = self.transition.borrow().as_ref() {
+            let (_, next) = RainEffect::create_textures(transition.image().clone(), blur);
+            rain_render.update_next_bg(&next);
+        }
+        rain_render.set_flash_textures(RainEffect::flash_textures(
+            &self.weather_data.borrow(),
+            blur,
+        ));
+        rain_render.update_textures();
+        rain_render.draw();
+    }
+
+    /// 选择视差的输入来源并设置增益，gain 为 0 时不再使用该来源
+    ///
+    /// 来源为 pointer（鼠标位置）、orientation（设备倾斜）、scroll（页面滚动），
+    /// 各来源按增益相加。iOS 上使用 orientation 前需要页面先取得权限。
+    ///
+    /// Example:
+    /// ```javascript
+    /// effect.set_parallax_source("pointer", 1.0);
+    /// effect.set_parallax_source("scroll", 0.5);
+    /// ```
+    pub fn set_parallax_source(&self, source: String, gain: f64) -> Result<(), JsValue> {
+        let source = ParallaxSource::from_name(&source)
+            .ok_or_else(|| JsValue::from(format!("unknown parallax source {}", source)))?;
+        if self.destroyed.get() {
+            return Ok(());
+        }
+
+        self.parallax.borrow_mut().set_gain(source, gain);
+        // 按新的来源重新注册监听
+        let mut listener = self.parallax_listener.borrow_mut();
+        listener.take();
+        if !self.parallax.borrow().sources().is_empty() {
+            *listener = Some(ParallaxListener::new(self.parallax.clone()));
+        }
+        Ok(())
+    }
 
-        *g.borrow_mut() = Some(Closure::wrap(Box::new(move || {
-            rain_drops.borrow_mut().draw();
+    /// 视差跟随输入的平滑时间（毫秒），0 为不平滑
+    pub fn set_parallax_smoothing(&self, smoothing: f64) {
+        self.parallax.borrow_mut().set_smoothing(smoothing);
+    }
+
+    /// 打开或关闭光敏性安全限制
+    ///
+    /// 按 WCAG 2.3.1 限制闪电等亮度变化：任意一秒内最多三次闪烁，
+    /// 亮度变化速度有上限，饱和的红色向灰色靠拢（只在 WebGL 下）。
+    /// 系统设置了 `prefers-reduced-motion` 时默认打开。
+    pub fn set_photosensitivity_limit(&self, enabled: bool) {
+        let opts = SafetyOptions::new();
+        *self.safety.borrow_mut() = enabled.then(|| FlashLimiter::new(opts));
+        self.rain_render
+            .borrow_mut()
+            .set_max_red_saturation(enabled.then_some(opts.max_red_saturation));
+    }
+
+    /// 立即打一次闪电，动画暂停时不显示
+    ///
+    /// 天气配置了闪电图片（如 `stormFlashFg`、`stormFlashBg`）时画面过渡到闪电图片，
+    /// 否则只提高亮度和雨滴的高光。
+    pub fn flash(&self) {
+        self.lightning.borrow_mut().trigger(now());
+    }
+
+    /// 使用视频代替天气图片作为前景和背景，视频开始播放后 Promise 完成
+    ///
+    /// 视频静音循环播放，每一帧按 `set_background_blur` 模糊背景；跨域的视频需要允许 CORS。
+    ///
+    /// Example:
+    /// ```javascript
+    /// effect.set_video("video/city-street.mp4");
+    /// ```
+    pub fn set_video(&self, src: String) -> Promise {
+        let video = self.video.clone();
+
+        future_to_promise(async move {
+            *video.borrow_mut() = Some(VideoSource::from_url(&src).await?);
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// 使用 MediaStream（如摄像头）作为前景和背景，开始播放后 Promise 完成
+    ///
+    /// Example:
+    /// ```javascript
+    /// const stream = await navigator.mediaDevices.getUserMedia({ video: true });
+    /// effect.set_media_stream(stream);
+    /// ```
+    pub fn set_media_stream(&self, stream: MediaStream) -> Promise {
+        let video = self.video.clone();
+
+        future_to_promise(async move {
+            *video.borrow_mut() = Some(VideoSource::from_stream(&stream).await?);
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// 停止使用视频，恢复当前天气的图片
+    pub fn clear_video(&self) {
+        if self.video.borrow_mut().take().is_none() {
+            return;
+        }
+        RainEffect::draw_weather(
+            &self.fg.borrow(),
+            &self.bg.borrow(),
+            &self.images.weather.borrow(),
+            self.blur.get(),
+        );
+        let rain_render = self.rain_render.borrow();
+        rain_render.update_weather_textures();
+        rain_render.draw();
+    }
+
+    /// 打开或关闭雨滴的高光（Canvas2D 下不描画）
+    pub fn set_shine(&self, enabled: bool) {
+        self.rain_render.borrow_mut().set_shine(enabled);
+        self.rain_render.borrow().draw();
+    }
+
+    /// 设置产生高光的光源
+    ///
+    /// direction 为光源方向（度，0 为右方，90 为上方），intensity 为 0.0 ~ 1.0，
+    /// color 为 `#rrggbb`。
+    ///
+    /// Example:
+    /// ```javascript
+    /// effect.set_light(135, 0.9, "#fff2d8");
+    /// ```
+    pub fn set_light(&self, direction: f64, intensity: f64, color: String) -> Result<(), JsValue> {
+        let color =
+            parse_color(&color).ok_or_else(|| JsValue::from(format!("invalid color {}", color)))?;
+        let light = Light {
+            direction,
+            intensity,
+            color,
+        };
+        self.rain_render.borrow_mut().set_light(light);
+        self.rain_render.borrow().draw();
+        Ok(())
+    }
+
+    /// 使用图片作为高光纹理，代替按光源生成的高光，图片加载完成后 Promise 完成
+    ///
+    /// 图片中心对应雨滴正面，亮斑偏向的方向就是高光在雨滴上的位置。
+    pub fn set_shine_image(&self, src: String) -> Promise {
+        let rain_render = self.rain_render.clone();
+
+        future_to_promise(async move {
+            let image = ImageFuture::new(&src)
+                .await
+                .map_err(|_| JsValue::from(format!("failed to load {}", src)))?;
+            rain_render.borrow_mut().set_shine_image(Some(image));
             rain_render.borrow().draw();
-            // console::log_1(&JsValue::from(now()));
-            // Schedule ourself for another requestAnimationFrame callback.
-            request_animation_frame(f.borrow().as_ref().unwrap());
-        }) as Box<dyn FnMut()>));
 
-        request_animation_frame(g.borrow().as_ref().unwrap());
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// 切换天气，在 duration 毫秒内平滑过渡
+    ///
+    /// 新天气的图片加载完成后 Promise 完成，过渡随动画帧推进。
+    /// 过渡途中再次切换时，从当前的中间状态开始新的过渡。
+    ///
+    /// Example:
+    /// ```javascript
+    /// effect.transition("sun", 3000).then(() => console.log("loaded"));
+    /// ```
+    pub fn transition(&self, weather: String, duration: f64) -> Promise {
+        let images = self.images.clone();
+        let rain_render = self.rain_render.clone();
+        let weather_data = self.weather_data.clone();
+        let transition = self.transition.clone();
+        let blur = self.blur.clone();
+
+        future_to_promise(async move {
+            let image = Rc::new(RefCell::new(images.load_weather(&weather).await));
+            let flash = RainEffect::load_flash(&images, &weather).await;
+            let (fg, bg) = RainEffect::create_textures(image.clone(), blur.get());
+
+            let now = now();
+            let running = transition.borrow_mut().take();
+            let from = match running {
+                Some(running) => {
+                    let opts = running.options(now);
+                    RainEffect::finish_transition(
+                        running,
+                        &images,
+                        &rain_render,
+                        &weather_data,
+                        blur.get(),
+                    );
+                    opts
+                }
+                None => weather_data.borrow().options().clone(),
+            };
+
+            rain_render.borrow_mut().prepare_transition(&fg, &bg);
+            let mut to = Weather::new_with_img(image.clone());
+            to.options_mut().flash = flash;
+            *transition.borrow_mut() = Some(Transition::new(from, to, image, now, duration));
+
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    fn apply_spawn(
+        weather_data: &RefCell<Weather>,
+        rain_drops: &RefCell<RainDrops>,
+        spawn: Rc<RefCell<dyn SpawnStrategy>>,
+    ) {
+        let mut weather_data = weather_data.borrow_mut();
+        weather_data.options_mut().spawn = Some(spawn);
+        rain_drops.borrow_mut().set_options(weather_data.options());
+    }
+
+    /// 继续暂停的动画，过渡和雨滴的时钟顺延暂停的时间
+    fn resume_animation(
+        paused_at: &Cell<Option<f64>>,
+        transition: &RefCell<Option<Transition>>,
+        rain_drops: &RefCell<RainDrops>,
+        back_pane: &RefCell<Option<RainDrops>>,
+        animation: &RefCell<Option<AnimationLoop>>,
+    ) {
+        if let Some(paused_at) = paused_at.take() {
+            if let Some(transition) = transition.borrow_mut().as_mut() {
+                transition.delay(now() - paused_at);
+            }
+        }
+        rain_drops.borrow_mut().reset_clock();
+        if let Some(back_pane) = back_pane.borrow_mut().as_mut() {
+            back_pane.reset_clock();
+        }
+
+        if let Some(animation) = animation.borrow().as_ref() {
+            animation.start();
+        }
+    }
+
+    /// 按 CSS 大小和当前的像素比调整画布、水面纹理和渲染分辨率
+    ///
+    /// 像素比只在画布大小上乘一次，雨滴模拟按像素比换算回 CSS 像素。
+    fn resize_canvas(
+        canvas: &RefCell<HtmlCanvasElement>,
+        rain_drops: &RefCell<RainDrops>,
+        back_pane: &RefCell<Option<RainDrops>>,
+        rain_render: &RefCell<RainRender>,
+        dpi: &Cell<f64>,
+        width: f64,
+        height: f64,
+    ) {
+        let ratio = window().unwrap().device_pixel_ratio();
+        let (w, h) = (
+            (width * ratio).round().max(1.0) as u32,
+            (height * ratio).round().max(1.0) as u32,
+        );
+        {
+            let canvas = canvas.borrow();
+            if (canvas.width(), canvas.height()) == (w, h) && dpi.get() == ratio {
+                return;
+            }
+            canvas.set_width(w);
+            canvas.set_height(h);
+        }
+        dpi.set(ratio);
+
+        rain_drops.borrow_mut().resize(w as f64, h as f64, ratio);
+        if let Some(back_pane) = back_pane.borrow_mut().as_mut() {
+            back_pane.resize(w as f64, h as f64, ratio);
+        }
+        let mut rain_render = rain_render.borrow_mut();
+        rain_render.resize(w, h);
+        // 改变画布大小会清空画面，立即重画一帧
+        rain_render.draw();
+    }
+
+    fn listen_resize(&self) -> ResizeListener {
+        let canvas = self.canvas.clone();
+        let rain_drops = self.rain_drops.clone();
+        let back_pane = self.back_pane.clone();
+        let rain_render = self.rain_render.clone();
+        let dpi = self.dpi.clone();
+        ResizeListener::new(&self.canvas.borrow(), move |width, height| {
+            RainEffect::resize_canvas(
+                &canvas,
+                &rain_drops,
+                &back_pane,
+                &rain_render,
+                &dpi,
+                width,
+                height,
+            );
+        })
+    }
+
+    /// WebGL 上下文丢失时像 `pause` 一样停止动画，恢复后重建 WebGL 资源并继续
+    ///
+    /// 雨滴模拟在 CPU 一侧，不会因为上下文丢失而改变；GPU 上的水面纹理重新创建后在下一帧重画。
+    fn listen_context_loss(&self) -> ContextLossListener {
+        let lost = {
+            let animation = self.animation.clone();
+            let paused_at = self.paused_at.clone();
+            let resume_on_restore = self.resume_on_restore.clone();
+            move || {
+                if let Some(animation) = animation.borrow().as_ref() {
+                    if animation.is_running() {
+                        animation.stop();
+                        paused_at.set(Some(now()));
+                        resume_on_restore.set(true);
+                    }
+                }
+            }
+        };
+
+        let restored = {
+            let rain_drops = self.rain_drops.clone();
+            let back_pane = self.back_pane.clone();
+            let rain_render = self.rain_render.clone();
+            let transition = self.transition.clone();
+            let animation = self.animation.clone();
+            let paused_at = self.paused_at.clone();
+            let resume_on_restore = self.resume_on_restore.clone();
+            move || {
+                if let Err(err) = rain_render.borrow_mut().restore() {
+                    console::error_1(&format!("failed to restore WebGL: {}", err).into());
+                    return;
+                }
+                rain_drops.borrow_mut().restore();
+                if let Some(back_pane) = back_pane.borrow_mut().as_mut() {
+                    back_pane.restore();
+                }
+                if resume_on_restore.replace(false) {
+                    RainEffect::resume_animation(
+                        &paused_at,
+                        &transition,
+                        &rain_drops,
+                        &back_pane,
+                        &animation,
+                    );
+                } else {
+                    // 暂停中：重画一帧，避免画面保持空白
+                    rain_render.borrow().draw();
+                }
+            }
+        };
+
+        ContextLossListener::new(&self.canvas.borrow(), lost, restored)
+    }
+
+    /// 推进天气过渡
+    fn update_transition(
+        transition: &RefCell<Option<Transition>>,
+        images: &Images,
+        rain_drops: &RefCell<RainDrops>,
+        rain_render: &RefCell<RainRender>,
+        weather_data: &RefCell<Weather>,
+        blur: BlurOptions,
+    ) {
+        let now = now();
+        let mut current = transition.borrow_mut();
+        if let Some(running) = current.as_ref() {
+            rain_drops.borrow_mut().set_options(&running.options(now));
+            rain_render
+                .borrow_mut()
+                .set_cross_fade(running.progress(now));
+
+            if running.is_finished(now) {
+                let running = current.take().unwrap();
+                RainEffect::finish_transition(running, images, rain_render, weather_data, blur);
+            }
+        }
+    }
+
+    /// 视频有新的一帧时画到前景和背景纹理上，过渡中同时更新过渡目标纹理
+    fn update_video(
+        video: &RefCell<Option<VideoSource>>,
+        fg: &RefCell<Texture>,
+        bg: &RefCell<Texture>,
+        transition: &RefCell<Option<Transition>>,
+        rain_render: &RefCell<RainRender>,
+        blur: BlurOptions,
+    ) {
+        let video = video.borrow();
+        let video = match video.as_ref() {
+            Some(video) if video.next_frame() => video,
+            _ => return,
+        };
+
+        let (fg, bg) = (fg.borrow(), bg.borrow());
+        let (w, h) = (FgSize::Width as u32 as f64, FgSize::Height as u32 as f64);
+        fg.ctx.clear_rect(0.0, 0.0, w, h);
+        video.draw(&fg.ctx, w, h);
+        // 每帧都要模糊，用画布的 filter 代替 draw_background 的 CPU 模糊
+        let (w, h) = (BgSize::Width as u32 as f64, BgSize::Height as u32 as f64);
+        bg.ctx.clear_rect(0.0, 0.0, w, h);
+        video.draw_blurred(&bg.ctx, w, h, blur);
+
+        let rain_render = rain_render.borrow();
+        if transition.borrow().is_some() {
+            rain_render.update_next_textures(&fg, &bg);
+        }
+        rain_render.update_weather_textures();
+    }
+
+    /// 按天气（过渡中为过渡的中间状态）的 flash_chance 打闪，并更新闪电的亮度
+    fn update_lightning(
+        lightning: &RefCell<Lightning>,
+        safety: &RefCell<Option<FlashLimiter>>,
+        transition: &RefCell<Option<Transition>>,
+        weather_data: &RefCell<Weather>,
+        rain_render: &RefCell<RainRender>,
+    ) {
+        let now = now();
+        let chance = match transition.borrow().as_ref() {
+            Some(running) => running.options(now).flash_chance,
+            None => weather_data.borrow().options().flash_chance,
+        };
+        let mut flash = lightning.borrow_mut().update(now, chance);
+        if let Some(limiter) = safety.borrow_mut().as_mut() {
+            flash = limiter.limit(now, flash);
+        }
+        rain_render.borrow_mut().set_flash(flash);
+    }
+
+    fn finish_transition(
+        transition: Transition,
+        images: &Images,
+        rain_render: &RefCell<RainRender>,
+        weather_data: &RefCell<Weather>,
+        blur: BlurOptions,
+    ) {
+        let mut rain_render = rain_render.borrow_mut();
+        rain_render.commit_transition();
+
+        let (weather, image) = transition.finish();
+        *images.weather.borrow_mut() = image.borrow().clone();
+        rain_render.set_flash_textures(RainEffect::flash_textures(&weather, blur));
+        *weather_data.borrow_mut() = weather;
+    }
+}
+
+impl RainEffect {
+    /// 使用自定义的雨点生成策略
+    pub fn set_spawn_strategy(&self, spawn: Rc<RefCell<dyn SpawnStrategy>>) {
+        RainEffect::apply_spawn(&self.weather_data, &self.rain_drops, spawn);
     }
 }
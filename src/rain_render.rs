@@ -1,13 +1,22 @@
+use crate::canvas_render::{CanvasRender, CanvasTextures};
+use crate::post_process::{PostEffect, PostProcess};
 use crate::shader::{FRAGMENT_SHADER, VERTEX_SHADER};
+use crate::shine::{shine_texture, Light};
 use crate::textures::Texture;
+use crate::water_map::WaterMapSource;
 use crate::webgl::{UniformType, WebGl};
-use crate::{compile_shader, create_canvas_element};
+use crate::{create_canvas_element, now};
 use std::cell::RefCell;
 use std::rc::Rc;
+use wasm_bindgen::Clamped;
 use wasm_bindgen::JsCast;
-use web_sys::{HtmlCanvasElement, WebGlRenderingContext};
+use web_sys::{console, HtmlCanvasElement, HtmlImageElement, ImageData, WebGl2RenderingContext};
 
 pub struct RainRenderOptions {
+    /// 雨滴的高光（Canvas2D 下不描画）
+    pub render_shine: bool,
+    /// 产生高光的光源
+    pub light: Light,
     pub render_shadow: bool,
     pub min_refraction: f64,
     pub max_refraction: f64,
@@ -16,11 +25,15 @@ pub struct RainRenderOptions {
     pub alpha_subtract: f64,
     pub parallax_bg: f64,
     pub parallax_fg: f64,
+    /// 双层玻璃中后层玻璃的视差
+    pub parallax_back: f64,
 }
 
 impl Default for RainRenderOptions {
     fn default() -> Self {
         RainRenderOptions {
+            render_shine: false,
+            light: Light::new(),
             render_shadow: false,
             min_refraction: 256.0,
             max_refraction: 512.0,
@@ -29,6 +42,7 @@ impl Default for RainRenderOptions {
             alpha_subtract: 5.0,
             parallax_bg: 5.0,
             parallax_fg: 20.0,
+            parallax_back: 10.0,
         }
     }
 }
@@ -37,6 +51,28 @@ impl RainRenderOptions {
     pub fn new() -> Self {
         RainRenderOptions::default()
     }
+
+    /// 窗户玻璃上的雨滴（页面默认效果）
+    pub fn window() -> Self {
+        RainRenderOptions {
+            render_shine: true,
+            brightness: 1.04,
+            alpha_multiply: 6.0,
+            alpha_subtract: 3.0,
+            ..RainRenderOptions::default()
+        }
+    }
+}
+
+// 高光纹理的大小
+pub(crate) const SHINE_SIZE: u32 = 64;
+// 闪电最亮时亮度提高的比例
+pub(crate) const FLASH_BRIGHTNESS: f64 = 0.5;
+
+/// 渲染方式：WebGL，或不支持 WebGL 时的 Canvas2D
+enum Backend {
+    WebGl(WebGl),
+    Canvas(CanvasRender),
 }
 
 pub struct RainRender {
@@ -45,12 +81,31 @@ pub struct RainRender {
     // 背景高度
     height: f64,
     effect_canvas: Rc<RefCell<HtmlCanvasElement>>,
-    drops_texture: Rc<RefCell<Texture>>,
+    // 水面纹理，由 `set_water_map` 设置
+    drops_texture: Option<WaterMapSource>,
+    // 后层玻璃的水面纹理
+    back_drops_texture: Option<WaterMapSource>,
     shine: Rc<RefCell<Texture>>,
+    // 加载的高光图片，None 时按光源生成
+    shine_image: Option<HtmlImageElement>,
     fg: Rc<RefCell<Texture>>,
     bg: Rc<RefCell<Texture>>,
+    // 过渡目标纹理
+    next_fg: Texture,
+    next_bg: Texture,
+    // 交叉淡入比例
+    cross_fade: f64,
+    // 闪电纹理，叠加了闪电纹理的当前纹理，闪电的亮度
+    flash_textures: Option<(Texture, Texture)>,
+    flashed: (Texture, Texture),
+    flash: f64,
+    // 红色饱和度上限，1.0 为不限制
+    max_red_saturation: f64,
     opts: RainRenderOptions,
-    gl: WebGl,
+    backend: Backend,
+    // 后处理效果和由其创建的通道图
+    post_effects: Vec<PostEffect>,
+    post: Option<PostProcess>,
     parallax_x: f64,
     parallax_y: f64,
 }
@@ -58,93 +113,519 @@ pub struct RainRender {
 impl RainRender {
     pub fn new(
         effect_canvas: Rc<RefCell<HtmlCanvasElement>>,
-        drops_texture: Rc<RefCell<Texture>>,
         fg: Rc<RefCell<Texture>>,
         bg: Rc<RefCell<Texture>>,
         opts: Option<RainRenderOptions>,
-    ) -> Self {
+    ) -> Result<Self, String> {
         let opts = match opts {
             Some(opts) => opts,
             None => RainRenderOptions::new(),
         };
-        let canvas = effect_canvas.borrow();
-        let (w, h) = (canvas.width() as f64, canvas.height() as f64);
+        let (shine, ctx) = create_canvas_element(SHINE_SIZE, SHINE_SIZE).unwrap();
+        // 过渡目标纹理，初始与当前纹理相同
+        let next_fg = RainRender::copy_texture(&fg.borrow());
+        let next_bg = RainRender::copy_texture(&bg.borrow());
+        let flashed = (
+            RainRender::copy_texture(&fg.borrow()),
+            RainRender::copy_texture(&bg.borrow()),
+        );
+
+        // 没有 WebGL 上下文时退回 Canvas2D；
+        // 上下文已创建但程序创建失败时画布不能再用于 Canvas2D，返回错误
+        let backend = match WebGl::new(effect_canvas.clone(), None) {
+            Ok(gl) => Backend::WebGl(gl),
+            Err(err) => match CanvasRender::new(&effect_canvas.borrow(), &opts) {
+                Some(canvas) => {
+                    console::warn_1(&format!("WebGL unavailable, using Canvas2D: {}", err).into());
+                    Backend::Canvas(canvas)
+                }
+                None => return Err(format!("WebGL failed and Canvas2D is unavailable: {}", err)),
+            },
+        };
 
-        let gl = WebGl::new(effect_canvas.clone(), None);
+        let (w, h) = {
+            let canvas = effect_canvas.borrow();
+            (canvas.width() as f64, canvas.height() as f64)
+        };
+        let render = RainRender {
+            width: w,
+            height: h,
+            effect_canvas,
+            drops_texture: None,
+            back_drops_texture: None,
+            shine: Rc::new(RefCell::new(Texture { canvas: shine, ctx })),
+            shine_image: None,
+            fg,
+            bg,
+            next_fg,
+            next_bg,
+            cross_fade: 0.0,
+            flash_textures: None,
+            flashed,
+            flash: 0.0,
+            max_red_saturation: 1.0,
+            opts,
+            backend,
+            post_effects: Vec::new(),
+            post: None,
+            parallax_x: 0.0,
+            parallax_y: 0.0,
+        };
+        render.draw_shine();
+        render.setup_webgl();
+        Ok(render)
+    }
+
+    // 设置 WebGL 的全部 uniform 和纹理
+    fn setup_webgl(&self) {
+        let gl = match self.gl() {
+            Some(gl) => gl,
+            None => return,
+        };
+        let opts = &self.opts;
+        let (w, h) = (self.width, self.height);
         let (bg_w, bg_h) = (
-            bg.borrow().canvas.width() as f64,
-            bg.borrow().canvas.height() as f64,
+            self.bg.borrow().canvas.width() as f64,
+            self.bg.borrow().canvas.height() as f64,
         );
 
         gl.create_uniform(UniformType::F2(w as f32, h as f32), "resolution");
         gl.create_uniform(UniformType::F1((bg_w / bg_h) as f32), "textureRatio");
-        gl.create_uniform(UniformType::I1(0), "renderShine");
+        gl.create_uniform(UniformType::I1(opts.render_shine as i32), "renderShine");
         gl.create_uniform(UniformType::I1(opts.render_shadow as i32), "renderShadow");
         gl.create_uniform(UniformType::F1(opts.min_refraction as f32), "minRefraction");
         gl.create_uniform(
             UniformType::F1((opts.max_refraction - opts.min_refraction) as f32),
             "refractionDelta",
         );
-        gl.create_uniform(UniformType::F1(opts.brightness as f32), "brightness");
+        gl.create_uniform(UniformType::F1(self.brightness() as f32), "brightness");
+        gl.create_uniform(UniformType::F1(self.flash as f32), "flash");
+        gl.create_uniform(
+            UniformType::F1(self.max_red_saturation as f32),
+            "maxRedSaturation",
+        );
         gl.create_uniform(UniformType::F1(opts.alpha_multiply as f32), "alphaMultiply");
         gl.create_uniform(UniformType::F1(opts.alpha_subtract as f32), "alphaSubtract");
         gl.create_uniform(UniformType::F1(opts.parallax_bg as f32), "parallaxBg");
         gl.create_uniform(UniformType::F1(opts.parallax_fg as f32), "parallaxFg");
+        gl.create_uniform(UniformType::F1(opts.parallax_back as f32), "parallaxBack");
 
-        gl.create_texture(None, 0);
+        gl.create_texture("waterMap", 0, None);
+        gl.create_texture("textureShine", 1, Some(&self.shine.borrow().canvas));
+        gl.create_texture("textureFg", 2, Some(&self.fg.borrow().canvas));
+        gl.create_texture("textureBg", 3, Some(&self.bg.borrow().canvas));
+        gl.create_texture("nextTextureFg", 4, Some(&self.next_fg.canvas));
+        gl.create_texture("nextTextureBg", 5, Some(&self.next_bg.canvas));
 
-        let (shine, ctx) = create_canvas_element(2, 2).unwrap();
+        gl.create_uniform(UniformType::F1(self.cross_fade as f32), "crossFade");
 
-        gl.create_texture(Some(&shine), 1);
-        gl.create_uniform(UniformType::I1(1), "textureShine");
+        // 后层玻璃的水面纹理
+        gl.create_texture("waterMapBack", 6, None);
+        gl.create_uniform(
+            UniformType::I1(self.back_drops_texture.is_some() as i32),
+            "renderBackPane",
+        );
+    }
 
-        gl.create_texture(Some(&bg.borrow().canvas), 2);
-        gl.create_uniform(UniformType::I1(2), "textureFg");
+    /// WebGL2 上下文，水面纹理可以在其中直接描画；WebGL1 和 Canvas2D 返回 None
+    pub fn webgl2(&self) -> Option<WebGl2RenderingContext> {
+        self.gl().and_then(|gl| gl.webgl2())
+    }
 
-        gl.create_texture(Some(&fg.borrow().canvas), 3);
-        gl.create_uniform(UniformType::I1(3), "textureBg");
+    /// 设置水面纹理
+    ///
+    /// GPU 上的水面纹理只能在 `webgl2` 返回的上下文中创建；Canvas2D 渲染只支持画布。
+    pub fn set_water_map(&mut self, source: WaterMapSource) {
+        self.drops_texture = Some(source);
+    }
 
-        RainRender {
-            width: w,
-            height: h,
-            effect_canvas: effect_canvas.clone(),
-            drops_texture,
-            shine: Rc::new(RefCell::new(Texture { canvas: shine, ctx })),
-            fg,
-            bg,
-            opts,
-            gl,
-            parallax_x: 0.0,
-            parallax_y: 0.0,
+    fn gl(&self) -> Option<&WebGl> {
+        match &self.backend {
+            Backend::WebGl(gl) => Some(gl),
+            Backend::Canvas(_) => None,
         }
     }
 
     pub fn draw(&self) {
-        self.gl.use_program();
-        self.gl.create_uniform(
+        let gl = match &self.backend {
+            Backend::WebGl(gl) => gl,
+            Backend::Canvas(canvas) => {
+                let drops_texture = match self.drops_texture.as_ref().and_then(|t| t.canvas()) {
+                    Some(texture) => texture.borrow(),
+                    None => return,
+                };
+                let back_drops_texture = self
+                    .back_drops_texture
+                    .as_ref()
+                    .and_then(|t| t.canvas())
+                    .map(|t| t.borrow());
+                let (fg, bg) = (self.fg.borrow(), self.bg.borrow());
+                let (fg, bg) = if self.is_flash_mixed() {
+                    (&self.flashed.0, &self.flashed.1)
+                } else {
+                    (&*fg, &*bg)
+                };
+                let textures = CanvasTextures {
+                    water_map: &drops_texture,
+                    fg,
+                    bg,
+                    water_map_back: back_drops_texture.as_deref(),
+                    next: (&self.next_fg, &self.next_bg),
+                };
+                canvas.draw(
+                    &textures,
+                    (self.parallax_x, self.parallax_y),
+                    self.cross_fade,
+                );
+                return;
+            }
+        };
+
+        gl.use_program();
+        gl.create_uniform(
             UniformType::F2(self.parallax_x as f32, self.parallax_y as f32),
             "parallax",
         );
 
         self.update_texture();
-        self.gl.draw();
+        match &self.post {
+            Some(post) => {
+                post.bind_scene();
+                gl.draw();
+                post.run((now() / 1000.0 % 1000.0) as f32);
+            }
+            None => gl.draw(),
+        }
+    }
+
+    /// 设置后处理效果，按顺序作用在雨的合成结果上，空列表为关闭
+    ///
+    /// 需要 WebGL2，WebGL1 和 Canvas2D 下忽略。颜色查找表的图片需要先加载完成。
+    pub fn set_post_effects(&mut self, effects: Vec<PostEffect>) {
+        self.post_effects = effects;
+        self.build_post_process();
+    }
+
+    // 按 post_effects 重新创建通道图
+    fn build_post_process(&mut self) {
+        self.post = None;
+        if self.post_effects.is_empty() {
+            return;
+        }
+        let gl = match self.webgl2() {
+            Some(gl) => gl,
+            None => {
+                console::warn_1(&"post-processing needs WebGL2".into());
+                return;
+            }
+        };
+        let (w, h) = (self.width as u32, self.height as u32);
+        match PostProcess::new(&gl, &self.post_effects, w, h) {
+            Ok(post) => {
+                post.set_max_red_saturation(self.max_red_saturation as f32);
+                self.post = Some(post);
+            }
+            Err(err) => {
+                console::error_1(&format!("failed to create post-processing: {}", err).into())
+            }
+        }
     }
 
     pub fn update_textures(&self) {
-        self.gl.active_texture(1);
-        self.gl.update_texture(&self.shine.borrow().canvas);
+        let gl = match self.gl() {
+            Some(gl) => gl,
+            None => return,
+        };
+        gl.update_texture("textureShine", &self.shine.borrow().canvas);
+        self.update_weather_textures();
+    }
+
+    /// 更新当前的前景和背景纹理，闪电时先叠加闪电纹理
+    pub fn update_weather_textures(&self) {
+        let mixed = self.is_flash_mixed();
+        if let Some((flash_fg, flash_bg)) = self.flash_textures.as_ref().filter(|_| mixed) {
+            RainRender::draw_texture(&self.flashed.0, &self.fg.borrow(), 1.0);
+            RainRender::blend_texture(&self.flashed.0, flash_fg, self.flash);
+            RainRender::draw_texture(&self.flashed.1, &self.bg.borrow(), 1.0);
+            RainRender::blend_texture(&self.flashed.1, flash_bg, self.flash);
+        }
+
+        if let Some(gl) = self.gl() {
+            let (fg, bg) = (self.fg.borrow(), self.bg.borrow());
+            let (fg, bg) = if mixed {
+                (&self.flashed.0, &self.flashed.1)
+            } else {
+                (&*fg, &*bg)
+            };
+            gl.update_texture("textureFg", &fg.canvas);
+            gl.update_texture("textureBg", &bg.canvas);
+        }
+    }
+
+    fn is_flash_mixed(&self) -> bool {
+        self.flash > 0.0 && self.flash_textures.is_some()
+    }
+
+    // 闪电时提高后的亮度
+    fn brightness(&self) -> f64 {
+        self.opts.brightness * (1.0 + FLASH_BRIGHTNESS * self.flash)
+    }
+
+    /// 设置视差（-1 ~ 1），下一帧生效
+    pub fn set_parallax(&mut self, x: f64, y: f64) {
+        self.parallax_x = x;
+        self.parallax_y = y;
+    }
 
-        self.gl.active_texture(2);
-        self.gl.update_texture(&self.fg.borrow().canvas);
+    /// 限制画面的红色饱和度 R / (R + G + B)，None 为不限制（只在 WebGL 下有效）
+    ///
+    /// 有后处理时，在画到画布上的最后一个通道中再限制一次。
+    pub fn set_max_red_saturation(&mut self, value: Option<f64>) {
+        self.max_red_saturation = value.map_or(1.0, |value| value.clamp(0.34, 1.0));
+        if let Some(gl) = self.gl() {
+            gl.use_program();
+            gl.create_uniform(
+                UniformType::F1(self.max_red_saturation as f32),
+                "maxRedSaturation",
+            );
+        }
+        if let Some(post) = self.post.as_ref() {
+            post.set_max_red_saturation(self.max_red_saturation as f32);
+        }
+    }
+
+    /// 设置闪电时叠加的前景和背景纹理，None 为闪电时只提高亮度
+    pub fn set_flash_textures(&mut self, textures: Option<(Texture, Texture)>) {
+        self.flash_textures = textures;
+        self.update_weather_textures();
+    }
+
+    /// 设置闪电的亮度（0.0 ~ 1.0）
+    ///
+    /// 按亮度叠加闪电纹理，并提高画面的亮度和雨滴的高光。
+    pub fn set_flash(&mut self, value: f64) {
+        let value = value.clamp(0.0, 1.0);
+        if value == self.flash {
+            return;
+        }
+        self.flash = value;
+        self.update_weather_textures();
 
-        self.gl.active_texture(3);
-        self.gl.update_texture(&self.bg.borrow().canvas);
+        let brightness = self.brightness();
+        match &mut self.backend {
+            Backend::WebGl(gl) => {
+                gl.use_program();
+                gl.create_uniform(UniformType::F1(brightness as f32), "brightness");
+                gl.create_uniform(UniformType::F1(value as f32), "flash");
+            }
+            Backend::Canvas(canvas) => canvas.set_brightness(brightness),
+        }
     }
 
     pub fn update_texture(&self) {
-        self.gl.active_texture(0);
-        self.gl.update_texture(&self.drops_texture.borrow().canvas);
+        let gl = match self.gl() {
+            Some(gl) => gl,
+            None => return,
+        };
+        let sources = [
+            ("waterMap", &self.drops_texture),
+            ("waterMapBack", &self.back_drops_texture),
+        ];
+        for (name, source) in sources {
+            match source {
+                Some(WaterMapSource::Canvas(texture)) => {
+                    gl.update_texture(name, &texture.borrow().canvas)
+                }
+                Some(WaterMapSource::Gpu(target)) => {
+                    gl.attach_texture(name, &target.borrow().texture)
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// WebGL 版本，使用 Canvas2D 时为 0
+    pub fn webgl_version(&self) -> u32 {
+        self.gl().map_or(0, |gl| gl.version())
+    }
+
+    pub fn float_render_targets(&self) -> bool {
+        self.gl().is_some_and(|gl| gl.float_render_targets())
+    }
+
+    /// 设置后层玻璃的水面纹理，None 为单层玻璃
+    pub fn set_back_pane(&mut self, texture: Option<WaterMapSource>) {
+        if let Some(gl) = self.gl() {
+            gl.use_program();
+            gl.create_uniform(UniformType::I1(texture.is_some() as i32), "renderBackPane");
+        }
+        self.back_drops_texture = texture;
+    }
+
+    /// 画布大小变化后更新分辨率（画布本身由调用方调整）
+    pub fn resize(&mut self, w: u32, h: u32) {
+        self.width = w as f64;
+        self.height = h as f64;
+        if let Backend::WebGl(gl) = &mut self.backend {
+            gl.resize(w, h);
+            gl.use_program();
+            gl.create_uniform(UniformType::F2(w as f32, h as f32), "resolution");
+        }
+        if let Some(post) = self.post.as_mut() {
+            if let Err(err) = post.resize(w, h) {
+                console::error_1(&format!("failed to resize post-processing: {}", err).into());
+                self.post = None;
+            }
+        }
+    }
+
+    /// WebGL 上下文是否已丢失
+    pub fn is_context_lost(&self) -> bool {
+        self.gl().is_some_and(|gl| gl.is_context_lost())
+    }
+
+    /// WebGL 上下文恢复后，按 `RainRenderOptions` 重新创建程序、缓冲区、纹理和 uniform
+    ///
+    /// 纹理从画布重新上传，模拟状态不受影响。
+    pub fn restore(&mut self) -> Result<(), String> {
+        if let Backend::WebGl(gl) = &mut self.backend {
+            gl.restore()?;
+        }
+        self.setup_webgl();
+        self.build_post_process();
+        Ok(())
+    }
+
+    /// 释放 WebGL 资源
+    pub fn destroy(&mut self) {
+        self.back_drops_texture = None;
+        self.post = None;
+        if let Backend::WebGl(gl) = &mut self.backend {
+            gl.destroy();
+        }
     }
 
     fn setup_weather(&self) {}
+
+    /// 准备过渡目标纹理
+    pub fn prepare_transition(&mut self, fg: &Texture, bg: &Texture) {
+        self.update_next_textures(fg, bg);
+        self.set_cross_fade(0.0);
+    }
+
+    /// 更新过渡目标纹理，不改变过渡的进度
+    pub fn update_next_textures(&self, fg: &Texture, bg: &Texture) {
+        RainRender::draw_texture(&self.next_fg, fg, 1.0);
+        RainRender::draw_texture(&self.next_bg, bg, 1.0);
+
+        if let Some(gl) = self.gl() {
+            gl.update_texture("nextTextureFg", &self.next_fg.canvas);
+            gl.update_texture("nextTextureBg", &self.next_bg.canvas);
+        }
+    }
+
+    /// 打开或关闭雨滴的高光
+    pub fn set_shine(&mut self, enabled: bool) {
+        self.opts.render_shine = enabled;
+        if let Some(gl) = self.gl() {
+            gl.use_program();
+            gl.create_uniform(UniformType::I1(enabled as i32), "renderShine");
+        }
+    }
+
+    /// 设置光源，重新生成高光纹理；使用加载的高光图片时只改变强度
+    pub fn set_light(&mut self, light: Light) {
+        self.opts.light = light;
+        self.draw_shine();
+    }
+
+    /// 使用加载的高光图片，None 为按光源生成
+    pub fn set_shine_image(&mut self, image: Option<HtmlImageElement>) {
+        self.shine_image = image;
+        self.draw_shine();
+    }
+
+    // 把高光图片或生成的高光画到高光纹理上
+    fn draw_shine(&self) {
+        let shine = self.shine.borrow();
+        let size = SHINE_SIZE as f64;
+        shine.ctx.clear_rect(0.0, 0.0, size, size);
+        match &self.shine_image {
+            Some(image) => {
+                shine
+                    .ctx
+                    .set_global_alpha(self.opts.light.intensity.clamp(0.0, 1.0));
+                shine
+                    .ctx
+                    .draw_image_with_html_image_element_and_dw_and_dh(image, 0.0, 0.0, size, size)
+                    .unwrap();
+                shine.ctx.set_global_alpha(1.0);
+            }
+            None => {
+                let image = shine_texture(SHINE_SIZE, &self.opts.light);
+                let data = ImageData::new_with_u8_clamped_array_and_sh(
+                    Clamped(&image.data),
+                    SHINE_SIZE,
+                    SHINE_SIZE,
+                )
+                .unwrap();
+                shine.ctx.put_image_data(&data, 0.0, 0.0).unwrap();
+            }
+        }
+
+        if let Some(gl) = self.gl() {
+            gl.update_texture("textureShine", &shine.canvas);
+        }
+    }
+
+    /// 只更新过渡目标的背景纹理，不改变过渡的进度
+    pub fn update_next_bg(&self, bg: &Texture) {
+        RainRender::draw_texture(&self.next_bg, bg, 1.0);
+        if let Some(gl) = self.gl() {
+            gl.update_texture("nextTextureBg", &self.next_bg.canvas);
+        }
+    }
+
+    /// 设置交叉淡入比例（0.0 为当前纹理，1.0 为目标纹理）
+    pub fn set_cross_fade(&mut self, value: f64) {
+        self.cross_fade = value.clamp(0.0, 1.0);
+        if let Some(gl) = self.gl() {
+            gl.use_program();
+            gl.create_uniform(UniformType::F1(self.cross_fade as f32), "crossFade");
+        }
+    }
+
+    /// 把当前的混合结果写回当前纹理，并结束交叉淡入
+    pub fn commit_transition(&mut self) {
+        let alpha = self.cross_fade;
+        RainRender::blend_texture(&self.fg.borrow(), &self.next_fg, alpha);
+        RainRender::blend_texture(&self.bg.borrow(), &self.next_bg, alpha);
+        self.update_weather_textures();
+
+        self.set_cross_fade(0.0);
+    }
+
+    fn copy_texture(src: &Texture) -> Texture {
+        let (canvas, ctx) = create_canvas_element(src.canvas.width(), src.canvas.height()).unwrap();
+        let texture = Texture { canvas, ctx };
+        RainRender::draw_texture(&texture, src, 1.0);
+        texture
+    }
+
+    /// 清空 dst 后绘制 src
+    fn draw_texture(dst: &Texture, src: &Texture, alpha: f64) {
+        let (w, h) = (dst.canvas.width() as f64, dst.canvas.height() as f64);
+        dst.ctx.clear_rect(0.0, 0.0, w, h);
+        RainRender::blend_texture(dst, src, alpha);
+    }
+
+    /// 以 alpha 透明度把 src 叠加到 dst 上
+    fn blend_texture(dst: &Texture, src: &Texture, alpha: f64) {
+        let (w, h) = (dst.canvas.width() as f64, dst.canvas.height() as f64);
+        dst.ctx.set_global_alpha(alpha);
+        dst.ctx
+            .draw_image_with_html_canvas_element_and_dw_and_dh(&src.canvas, 0.0, 0.0, w, h)
+            .unwrap();
+        dst.ctx.set_global_alpha(1.0);
+    }
 }
@@ -0,0 +1,124 @@
+use js_sys::Array;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlCanvasElement, MediaQueryList, ResizeObserver, ResizeObserverEntry};
+
+type ResizeCallback = Rc<dyn Fn(f64, f64)>;
+
+/// 监听画布的 CSS 大小和 devicePixelRatio 的变化
+///
+/// CSS 大小由 ResizeObserver 监听；像素比的变化（窗口移到另一块屏幕、页面缩放）由
+/// `(resolution: Ndppx)` 媒体查询监听。两种变化都以画布的 CSS 大小回调，监听在析构时移除。
+///
+/// Example:
+/// ```rust
+/// let listener = ResizeListener::new(&canvas, move |w, h| {
+///     let dpi = window().unwrap().device_pixel_ratio();
+///     canvas.set_width((w * dpi) as u32);
+/// });
+/// ```
+pub struct ResizeListener {
+    observer: ResizeObserver,
+    _on_resize: Closure<dyn FnMut(Array)>,
+    pixel_ratio: Rc<PixelRatioWatch>,
+}
+
+impl ResizeListener {
+    pub fn new(canvas: &HtmlCanvasElement, on_resize: impl Fn(f64, f64) + 'static) -> Self {
+        let on_resize: ResizeCallback = Rc::new(on_resize);
+        // 最近一次的 CSS 大小，像素比变化时使用
+        let size = Rc::new(Cell::new((
+            canvas.client_width() as f64,
+            canvas.client_height() as f64,
+        )));
+
+        let callback = on_resize.clone();
+        let last = size.clone();
+        let on_observe = Closure::wrap(Box::new(move |entries: Array| {
+            let entry = match entries.pop().dyn_into::<ResizeObserverEntry>() {
+                Ok(entry) => entry,
+                Err(_) => return,
+            };
+            let rect = entry.content_rect();
+            last.set((rect.width(), rect.height()));
+            callback(rect.width(), rect.height());
+        }) as Box<dyn FnMut(Array)>);
+        let observer = ResizeObserver::new(on_observe.as_ref().unchecked_ref()).unwrap();
+        observer.observe(canvas);
+
+        let pixel_ratio = PixelRatioWatch::new(move || {
+            let (w, h) = size.get();
+            on_resize(w, h);
+        });
+
+        ResizeListener {
+            observer,
+            _on_resize: on_observe,
+            pixel_ratio,
+        }
+    }
+}
+
+impl Drop for ResizeListener {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+        self.pixel_ratio.unlisten();
+    }
+}
+
+/// 监听 devicePixelRatio 的变化
+///
+/// 媒体查询只能匹配注册时的像素比，所以每次变化后按新的像素比重新注册同一个回调。
+struct PixelRatioWatch {
+    query: RefCell<Option<MediaQueryList>>,
+    on_change: RefCell<Option<Closure<dyn FnMut()>>>,
+}
+
+impl PixelRatioWatch {
+    fn new(on_change: impl Fn() + 'static) -> Rc<Self> {
+        let watch = Rc::new(PixelRatioWatch {
+            query: RefCell::new(None),
+            on_change: RefCell::new(None),
+        });
+
+        // 回调只持有弱引用，避免循环引用
+        let weak: Weak<PixelRatioWatch> = Rc::downgrade(&watch);
+        *watch.on_change.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+            if let Some(watch) = weak.upgrade() {
+                watch.listen();
+                on_change();
+            }
+        }) as Box<dyn FnMut()>));
+        watch.listen();
+        watch
+    }
+
+    // 按当前像素比注册媒体查询
+    fn listen(&self) {
+        self.unlisten();
+        let on_change = self.on_change.borrow();
+        let on_change = match on_change.as_ref() {
+            Some(on_change) => on_change,
+            None => return,
+        };
+
+        let window = window().unwrap();
+        let media = format!("(resolution: {}dppx)", window.device_pixel_ratio());
+        if let Ok(Some(query)) = window.match_media(&media) {
+            query
+                .add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref())
+                .unwrap();
+            *self.query.borrow_mut() = Some(query);
+        }
+    }
+
+    fn unlisten(&self) {
+        let query = self.query.borrow_mut().take();
+        if let (Some(query), Some(on_change)) = (query, self.on_change.borrow().as_ref()) {
+            let _ = query
+                .remove_event_listener_with_callback("change", on_change.as_ref().unchecked_ref());
+        }
+    }
+}
@@ -0,0 +1,188 @@
+/// RGBA 像素缓冲区（每像素 4 字节，按行存储，第 0 行在最上方）
+///
+/// Example:
+/// ```rust
+/// let mut image = RgbaImage::new(640, 320);
+/// image.set_pixel(0, 0, [255, 0, 0, 255]);
+/// let [r, g, b, a] = image.sample(0.5, 0.5);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// 全透明的图像
+    pub fn new(width: u32, height: u32) -> Self {
+        RgbaImage::filled(width, height, [0, 0, 0, 0])
+    }
+
+    pub fn filled(width: u32, height: u32, color: [u8; 4]) -> Self {
+        let data = color
+            .iter()
+            .copied()
+            .cycle()
+            .take(width as usize * height as usize * 4)
+            .collect();
+        RgbaImage {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// 由像素数据创建，长度不等于 width * height * 4 时返回 None
+    pub fn from_raw(width: u32, height: u32, data: Vec<u8>) -> Option<Self> {
+        if data.len() != width as usize * height as usize * 4 {
+            return None;
+        }
+        Some(RgbaImage {
+            width,
+            height,
+            data,
+        })
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = self.index(x, y);
+        [
+            self.data[i],
+            self.data[i + 1],
+            self.data[i + 2],
+            self.data[i + 3],
+        ]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let i = self.index(x, y);
+        self.data[i..i + 4].copy_from_slice(&color);
+    }
+
+    pub fn clear(&mut self) {
+        self.data.fill(0);
+    }
+
+    /// 双线性采样，坐标为 0.0 ~ 1.0，超出范围时取边缘（与 WebGL 的 LINEAR + CLAMP_TO_EDGE 一致）
+    ///
+    /// 返回 0.0 ~ 1.0 的颜色值
+    pub fn sample(&self, u: f32, v: f32) -> [f32; 4] {
+        self.bilinear(u, v, false)
+    }
+
+    /// 按预乘透明度的颜色双线性采样，与以 `UNPACK_PREMULTIPLY_ALPHA_WEBGL` 上传的纹理一致
+    ///
+    /// 返回预乘后的 0.0 ~ 1.0 的颜色值
+    pub fn sample_premultiplied(&self, u: f32, v: f32) -> [f32; 4] {
+        self.bilinear(u, v, true)
+    }
+
+    fn bilinear(&self, u: f32, v: f32, premultiply: bool) -> [f32; 4] {
+        if self.width == 0 || self.height == 0 {
+            return [0.0; 4];
+        }
+
+        let x = u * self.width as f32 - 0.5;
+        let y = v * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let clamp_x = |x: f32| x.clamp(0.0, (self.width - 1) as f32) as u32;
+        let clamp_y = |y: f32| y.clamp(0.0, (self.height - 1) as f32) as u32;
+        let (xa, xb) = (clamp_x(x0), clamp_x(x0 + 1.0));
+        let (ya, yb) = (clamp_y(y0), clamp_y(y0 + 1.0));
+
+        let pixel = |x: u32, y: u32| {
+            let p = self.pixel(x, y).map(|c| c as f32);
+            if premultiply {
+                let a = p[3] / 255.0;
+                [p[0] * a, p[1] * a, p[2] * a, p[3]]
+            } else {
+                p
+            }
+        };
+        let (p00, p10) = (pixel(xa, ya), pixel(xb, ya));
+        let (p01, p11) = (pixel(xa, yb), pixel(xb, yb));
+
+        let mut color = [0.0; 4];
+        for (i, c) in color.iter_mut().enumerate() {
+            let top = p00[i] + (p10[i] - p00[i]) * fx;
+            let bottom = p01[i] + (p11[i] - p01[i]) * fx;
+            *c = (top + (bottom - top) * fy) / 255.0;
+        }
+        color
+    }
+
+    /// 与另一张同样大小的图片按 t（0.0 ~ 1.0）混合，大小不同时返回 None
+    pub fn mix(&self, other: &RgbaImage, t: f64) -> Option<RgbaImage> {
+        if self.width != other.width || self.height != other.height {
+            return None;
+        }
+        let t = t.clamp(0.0, 1.0);
+        let data = self
+            .data
+            .iter()
+            .zip(other.data.iter())
+            .map(|(&a, &b)| (a as f64 + (b as f64 - a as f64) * t).round() as u8)
+            .collect();
+        Some(RgbaImage {
+            width: self.width,
+            height: self.height,
+            data,
+        })
+    }
+
+    /// 缩放到指定大小（与 Canvas2D 的 drawImage 缩放相近）
+    pub fn resize(&self, width: u32, height: u32) -> RgbaImage {
+        let mut image = RgbaImage::new(width, height);
+        if self.width == 0 || self.height == 0 {
+            return image;
+        }
+
+        // 缩小时先按整数倍做盒式平均，避免采样走样
+        let step_x = (self.width / width.max(1)).max(1);
+        let step_y = (self.height / height.max(1)).max(1);
+        let source = if step_x > 1 || step_y > 1 {
+            self.box_downsample(step_x, step_y)
+        } else {
+            self.clone()
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                let color = source.sample(u, v);
+                image.set_pixel(x, y, color.map(|c| (c * 255.0).round() as u8));
+            }
+        }
+        image
+    }
+
+    fn box_downsample(&self, step_x: u32, step_y: u32) -> RgbaImage {
+        let (width, height) = ((self.width / step_x).max(1), (self.height / step_y).max(1));
+        let mut image = RgbaImage::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0u32; 4];
+                let mut count = 0;
+                for sy in (y * step_y)..((y + 1) * step_y).min(self.height) {
+                    for sx in (x * step_x)..((x + 1) * step_x).min(self.width) {
+                        let p = self.pixel(sx, sy);
+                        for i in 0..4 {
+                            sum[i] += p[i] as u32;
+                        }
+                        count += 1;
+                    }
+                }
+                image.set_pixel(x, y, sum.map(|s| (s / count.max(1)) as u8));
+            }
+        }
+        image
+    }
+}
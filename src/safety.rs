@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+
+/// 光敏性安全限制的参数，默认值按 WCAG 2.3.1（三次闪烁阈值）
+///
+/// Example:
+/// ```rust
+/// let opts = SafetyOptions {
+///     max_flashes: 2.0,
+///     ..SafetyOptions::new()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SafetyOptions {
+    /// 任意一秒内最多的闪烁次数（一次闪烁为一对相反方向的亮度变化）
+    pub max_flashes: f64,
+    /// 亮度变化达到此值才算一次变化（相对亮度的 10%）
+    pub flash_threshold: f64,
+    /// 每秒亮度的最大变化量
+    pub max_change: f64,
+    /// 红色饱和度上限 R / (R + G + B)，WCAG 以 0.8 为饱和红色
+    pub max_red_saturation: f64,
+}
+
+impl Default for SafetyOptions {
+    fn default() -> Self {
+        SafetyOptions {
+            max_flashes: 3.0,
+            flash_threshold: 0.1,
+            max_change: 4.0,
+            max_red_saturation: 0.8,
+        }
+    }
+}
+
+impl SafetyOptions {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+// 留在阈值以下的余量
+const THRESHOLD_MARGIN: f64 = 0.001;
+
+/// 限制闪电等亮度动画的闪烁频率和变化速度
+///
+/// 亮度（0.0 ~ 1.0）先按 `max_change` 限制变化速度；同一方向上变化达到
+/// `flash_threshold` 记为一次变化，一秒内的变化次数用完后，新的变化保持在阈值以下，
+/// 不再构成闪烁。
+///
+/// Example:
+/// ```rust
+/// let mut limiter = FlashLimiter::new(SafetyOptions::new());
+/// let flash = limiter.limit(now(), lightning.update(now(), chance));
+/// ```
+pub struct FlashLimiter {
+    opts: SafetyOptions,
+    current: f64,
+    last: Option<f64>,
+    // 当前方向（true 为变亮）、开始时的亮度和是否已经记为一次变化
+    rising: Option<bool>,
+    run_start: f64,
+    counted: bool,
+    // 最近一秒内各次变化的时刻
+    transitions: VecDeque<f64>,
+}
+
+impl FlashLimiter {
+    pub fn new(opts: SafetyOptions) -> Self {
+        FlashLimiter {
+            opts,
+            current: 0.0,
+            last: None,
+            rising: None,
+            run_start: 0.0,
+            counted: false,
+            transitions: VecDeque::new(),
+        }
+    }
+
+    /// now（毫秒）时刻希望的亮度 value，返回限制后的亮度
+    pub fn limit(&mut self, now: f64, value: f64) -> f64 {
+        let dt = self.last.map_or(0.0, |last| (now - last).max(0.0)) / 1000.0;
+        self.last = Some(now);
+        let step = self.opts.max_change * dt;
+        let mut value = value.clamp(self.current - step, self.current + step);
+
+        while self.transitions.front().is_some_and(|&t| now - t >= 1000.0) {
+            self.transitions.pop_front();
+        }
+
+        if value != self.current {
+            let rising = value > self.current;
+            if self.rising != Some(rising) {
+                self.rising = Some(rising);
+                self.run_start = self.current;
+                self.counted = false;
+            }
+
+            let threshold = self.opts.flash_threshold;
+            if !self.counted && (value - self.run_start).abs() >= threshold {
+                let max_transitions = (self.opts.max_flashes * 2.0).floor() as usize;
+                if self.transitions.len() < max_transitions {
+                    self.transitions.push_back(now);
+                    self.counted = true;
+                } else {
+                    let limit = threshold - THRESHOLD_MARGIN;
+                    value = if rising {
+                        value.min(self.run_start + limit)
+                    } else {
+                        value.max(self.run_start - limit)
+                    };
+                }
+            }
+        }
+
+        self.current = value;
+        value
+    }
+}
@@ -1,3 +1,5 @@
+use web_sys::WebGlRenderingContext;
+
 pub static VERTEX_SHADER: &str = r#"
 precision mediump float;
 attribute vec2 a_position;
@@ -11,9 +13,14 @@ precision mediump float;
 
 // textures
 uniform sampler2D u_waterMap;
+// water map of the back pane of a double-pane window
+uniform sampler2D u_waterMapBack;
 uniform sampler2D u_textureShine;
 uniform sampler2D u_textureFg;
 uniform sampler2D u_textureBg;
+// textures of the weather being transitioned to
+uniform sampler2D u_nextTextureFg;
+uniform sampler2D u_nextTextureBg;
 
 // the texCoords passed in from the vertex shader.
 varying vec2 v_texCoord;
@@ -21,14 +28,21 @@ uniform vec2 u_resolution;
 uniform vec2 u_parallax;
 uniform float u_parallaxFg;
 uniform float u_parallaxBg;
+uniform float u_parallaxBack;
 uniform float u_textureRatio;
 uniform bool u_renderShine;
 uniform bool u_renderShadow;
+uniform bool u_renderBackPane;
 uniform float u_minRefraction;
 uniform float u_refractionDelta;
 uniform float u_brightness;
 uniform float u_alphaMultiply;
 uniform float u_alphaSubtract;
+uniform float u_crossFade;
+// brightness of a lightning flash
+uniform float u_flash;
+// upper limit of R/(R+G+B), 1.0 turns the limit off
+uniform float u_maxRedSaturation;
 
 // alpha-blends two colors
 vec4 blend(vec4 bg,vec4 fg){
@@ -45,6 +59,25 @@ vec4 blend(vec4 bg,vec4 fg){
   return vec4(rgb,a);
 }
 
+// moves saturated reds towards grey, keeping the brightness
+vec4 limitRed(vec4 c){
+  float sum=c.r+c.g+c.b;
+  float maxRed=u_maxRedSaturation*sum;
+  if(c.r>maxRed){
+    c.rgb=mix(c.rgb,vec3(sum/3.0),(c.r-maxRed)/(c.r-sum/3.0));
+  }
+  return c;
+}
+
+// cross-fades the current and next weather textures
+vec4 textureFg(vec2 pos){
+  return mix(texture2D(u_textureFg,pos),texture2D(u_nextTextureFg,pos),u_crossFade);
+}
+
+vec4 textureBg(vec2 pos){
+  return mix(texture2D(u_textureBg,pos),texture2D(u_nextTextureBg,pos),u_crossFade);
+}
+
 vec2 pixel(){
   return vec2(1.0,1.0)/u_resolution;
 }
@@ -58,7 +91,7 @@ vec2 texCoord(){
 }
 
 // scales the bg up and proportionally to fill the container
-vec2 scaledTexCoord(){
+vec2 scaledTexCoord(vec2 coord){
   float ratio=u_resolution.x/u_resolution.y;
   vec2 scale=vec2(1.0,1.0);
   vec2 offset=vec2(0.0,0.0);
@@ -70,30 +103,72 @@ vec2 scaledTexCoord(){
     scale.x=(1.0-ratioDelta);
     offset.x=-ratioDelta/2.0;
   }
-  return (texCoord()+offset)/scale;
+  return (coord+offset)/scale;
 }
 
-// get color from fg
-vec4 fgColor(float x, float y){
-  float p2=u_parallaxFg*2.0;
+vec2 scaledTexCoord(){
+  return scaledTexCoord(texCoord());
+}
+
+// position on a water map that moves with the given parallax
+vec2 paneCoord(vec2 coord, float parallaxPane){
+  float p2=parallaxPane*2.0;
   vec2 scale=vec2(
     (u_resolution.x+p2)/u_resolution.x,
     (u_resolution.y+p2)/u_resolution.y
   );
 
-  vec2 scaledTexCoord=texCoord()/scale;
+  vec2 scaledTexCoord=coord/scale;
   vec2 offset=vec2(
     (1.0-(1.0/scale.x))/2.0,
     (1.0-(1.0/scale.y))/2.0
   );
 
-  return texture2D(u_waterMap,
-    (scaledTexCoord+offset)+(pixel()*vec2(x,y))+parallax(u_parallaxFg)
+  return (scaledTexCoord+offset)+parallax(parallaxPane);
+}
+
+// textures are uploaded or drawn with premultiplied alpha
+vec4 unpremultiply(vec4 c){
+  if(c.a>0.0){
+    c.rgb/=c.a;
+  }
+  return c;
+}
+
+vec4 waterMap(sampler2D map, vec2 pos){
+  return unpremultiply(texture2D(map,pos));
+}
+
+// get color from fg
+vec4 fgColor(float x, float y){
+  return waterMap(u_waterMap,
+    paneCoord(texCoord(),u_parallaxFg)+(pixel()*vec2(x,y))
   );
 }
 
+// composites the back pane's drops at coord over what lies behind them
+vec4 backPane(vec2 coord, vec4 base, float brightness){
+  if(!u_renderBackPane){
+    return base;
+  }
+
+  vec4 cur=waterMap(u_waterMapBack,paneCoord(coord,u_parallaxBack));
+
+  float d=cur.b;
+  float a=clamp(cur.a*u_alphaMultiply-u_alphaSubtract, 0.0,1.0);
+
+  vec2 refraction=(vec2(cur.g,cur.r)-0.5)*2.0;
+  vec2 refractionPos=scaledTexCoord(coord)
+    + (pixel()*refraction*(u_minRefraction+(d*u_refractionDelta)))
+    + parallax(u_parallaxBg-u_parallaxBack);
+
+  vec4 tex=textureFg(refractionPos);
+  return blend(base,vec4(tex.rgb*brightness,a));
+}
+
 void main() {
-  vec4 bg=texture2D(u_textureBg,scaledTexCoord()+parallax(u_parallaxBg));
+  vec4 bg=textureBg(scaledTexCoord()+parallax(u_parallaxBg));
+  bg=backPane(texCoord(),bg,u_brightness);
 
   vec4 cur = fgColor(0.0,0.0);
 
@@ -109,13 +184,16 @@ void main() {
     + (pixel()*refraction*(u_minRefraction+(d*u_refractionDelta)))
     + refractionParallax;
 
-  vec4 tex=texture2D(u_textureFg,refractionPos);
+  vec4 tex=textureFg(refractionPos);
+  // the back pane seen through the front pane's drops
+  tex=backPane(texCoord()+(pixel()*refraction*(u_minRefraction+(d*u_refractionDelta))),tex,1.0);
 
   if(u_renderShine){
     float maxShine=490.0;
     float minShine=maxShine*0.18;
     vec2 shinePos=vec2(0.5,0.5) + ((1.0/512.0)*refraction)* -(minShine+((maxShine-minShine)*d));
-    vec4 shine=texture2D(u_textureShine,shinePos);
+    vec4 shine=unpremultiply(texture2D(u_textureShine,shinePos));
+    shine.a=min(shine.a*(1.0+u_flash*2.0),1.0);
     tex=blend(tex,shine);
   }
 
@@ -130,6 +208,23 @@ void main() {
     fg=blend(border,fg);
   }
 
-  gl_FragColor = blend(bg,fg);
+  gl_FragColor = limitRed(blend(bg,fg));
 }
 "#;
+
+/// 把 GLSL ES 1.00 着色器转换为 GLSL ES 3.00（WebGL2）
+///
+/// 只在开头加上版本声明和宏，着色器源码只维护一份。
+///
+/// Example:
+/// ```rust
+/// let source = glsl3(FRAGMENT_SHADER, WebGl2RenderingContext::FRAGMENT_SHADER);
+/// ```
+pub fn glsl3(source: &str, shader_type: u32) -> String {
+    let prefix = if shader_type == WebGlRenderingContext::VERTEX_SHADER {
+        "#version 300 es\n#define attribute in\n#define varying out\n#define texture2D texture\n"
+    } else {
+        "#version 300 es\n#define varying in\nout highp vec4 fragColor;\n#define gl_FragColor fragColor\n#define texture2D texture\n"
+    };
+    format!("{}{}", prefix, source)
+}
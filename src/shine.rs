@@ -0,0 +1,88 @@
+use crate::rgba::RgbaImage;
+
+/// 照在雨滴上的光，决定高光的位置、亮度和颜色
+///
+/// Example:
+/// ```rust
+/// let light = Light {
+///     direction: 45.0,
+///     color: [255, 220, 180],
+///     ..Light::new()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+    /// 光源方向（度），0 为右方，90 为上方
+    pub direction: f64,
+    /// 高光强度 0.0 ~ 1.0
+    pub intensity: f64,
+    /// 光的颜色
+    pub color: [u8; 3],
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Light {
+            direction: 120.0,
+            intensity: 0.8,
+            color: [255, 255, 255],
+        }
+    }
+}
+
+impl Light {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+// 亮斑中心到纹理中心的距离和亮斑的半径（纹理坐标）
+const SPOT_OFFSET: f64 = 0.22;
+const SPOT_RADIUS: f64 = 0.09;
+
+/// 生成 size x size 的高光纹理
+///
+/// 着色器按雨滴表面的折射方向在纹理中心附近取样，
+/// 偏向光源方向的亮斑就成为雨滴朝光一侧的高光。
+///
+/// Example:
+/// ```rust
+/// let shine = shine_texture(64, &Light::new());
+/// ```
+pub fn shine_texture(size: u32, light: &Light) -> RgbaImage {
+    let mut image = RgbaImage::new(size, size);
+    let angle = light.direction.to_radians();
+    // 纹理的 y 轴朝下
+    let (cx, cy) = (
+        0.5 + angle.cos() * SPOT_OFFSET,
+        0.5 - angle.sin() * SPOT_OFFSET,
+    );
+    let intensity = light.intensity.clamp(0.0, 1.0);
+    let [r, g, b] = light.color;
+
+    for y in 0..size {
+        for x in 0..size {
+            let u = (x as f64 + 0.5) / size as f64;
+            let v = (y as f64 + 0.5) / size as f64;
+            let d = (u - cx).hypot(v - cy) / SPOT_RADIUS;
+            let alpha = intensity * (-d * d).exp();
+            image.set_pixel(x, y, [r, g, b, (alpha * 255.0).round() as u8]);
+        }
+    }
+    image
+}
+
+/// 解析 `#rrggbb` 形式的颜色
+///
+/// Example:
+/// ```rust
+/// assert_eq!(parse_color("#ffcc00"), Some([255, 204, 0]));
+/// ```
+pub fn parse_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
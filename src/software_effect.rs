@@ -0,0 +1,182 @@
+use crate::rain_drops::{RainDrops, RainDropsOptions};
+use crate::rain_render::{RainRenderOptions, SHINE_SIZE};
+use crate::rgba::RgbaImage;
+use crate::shine::shine_texture;
+use crate::software_render::{SoftwareRender, SoftwareTextures};
+use crate::software_water_map::{DropSprites, SoftwareWaterMap};
+use crate::terminal::{self, TerminalMode};
+use crate::textures::{BgSize, FgSize};
+use crate::weather::WeatherOptions;
+use std::rc::Rc;
+
+/// 软件渲染所需的图片
+pub struct SoftwareImages {
+    pub fg: RgbaImage,
+    pub bg: RgbaImage,
+    pub drop_alpha: RgbaImage,
+    pub drop_color: RgbaImage,
+}
+
+pub struct SoftwareEffectOptions {
+    /// 输出宽度（像素）
+    pub width: u32,
+    /// 输出高度（像素）
+    pub height: u32,
+    /// 像素密度（与 devicePixelRatio 相同，雨滴按此放大）
+    pub dpi: f64,
+    /// 随机数种子（None 时每次运行都不同）
+    pub seed: Option<u64>,
+}
+
+impl Default for SoftwareEffectOptions {
+    fn default() -> Self {
+        SoftwareEffectOptions {
+            width: 1280,
+            height: 720,
+            dpi: 1.0,
+            seed: None,
+        }
+    }
+}
+
+impl SoftwareEffectOptions {
+    pub fn new() -> Self {
+        SoftwareEffectOptions::default()
+    }
+}
+
+/// 不依赖浏览器的雨滴效果：同样的模拟，由 `SoftwareRender` 渲染成图片
+///
+/// Example:
+/// ```rust
+/// let mut effect = SoftwareEffect::new("rain", &images, None);
+/// effect.warm_up(20.0);
+/// for _ in 0..60 {
+///     effect.step(1.0);
+///     let frame = effect.render();
+/// }
+/// ```
+pub struct SoftwareEffect {
+    width: u32,
+    height: u32,
+    rain_drops: RainDrops<SoftwareWaterMap>,
+    render: SoftwareRender,
+    fg: RgbaImage,
+    bg: RgbaImage,
+    // 雨滴高光，不渲染高光时为 None
+    shine: Option<RgbaImage>,
+}
+
+impl SoftwareEffect {
+    /// weather 为天气预设名称（rain / fallout / storm / sun / drizzle）
+    pub fn new(
+        weather: &str,
+        images: &SoftwareImages,
+        opts: Option<SoftwareEffectOptions>,
+    ) -> Self {
+        let opts = opts.unwrap_or_default();
+        let (w, h) = (opts.width, opts.height);
+
+        let sprites = Rc::new(DropSprites::new(&images.drop_alpha, &images.drop_color));
+        let rain_drops_opts = RainDropsOptions {
+            seed: opts.seed,
+            ..RainDropsOptions::window()
+        };
+        let mut rain_drops = RainDrops::with_water_maps(
+            w as f64,
+            h as f64,
+            opts.dpi,
+            SoftwareWaterMap::new(w, h, sprites.clone()),
+            SoftwareWaterMap::new(w, h, sprites),
+            Some(rain_drops_opts),
+        );
+        rain_drops.set_options(&WeatherOptions::preset(weather));
+
+        let render_opts = RainRenderOptions::window();
+        let shine = render_opts
+            .render_shine
+            .then(|| shine_texture(SHINE_SIZE, &render_opts.light));
+
+        SoftwareEffect {
+            width: w,
+            height: h,
+            rain_drops,
+            render: SoftwareRender::new(Some(render_opts)),
+            fg: images
+                .fg
+                .resize(FgSize::Width as u32, FgSize::Height as u32),
+            bg: images
+                .bg
+                .resize(BgSize::Width as u32, BgSize::Height as u32),
+            shine,
+        }
+    }
+
+    /// 调整输出大小，已有的雨滴按比例移到新的位置
+    pub fn resize(&mut self, width: u32, height: u32, dpi: f64) {
+        self.width = width;
+        self.height = height;
+        self.rain_drops.resize(width as f64, height as f64, dpi);
+    }
+
+    /// 推进一帧（1.0 为 60FPS 下的一帧）
+    pub fn step(&mut self, time_scale: f64) {
+        self.rain_drops.step(time_scale);
+    }
+
+    /// 预热：推进 seconds 秒的模拟时间
+    pub fn warm_up(&mut self, seconds: f64) {
+        self.rain_drops.warm_up(seconds);
+    }
+
+    /// 渲染当前帧
+    pub fn render(&self) -> RgbaImage {
+        let water_map = self.rain_drops.water_map();
+        let textures = SoftwareTextures {
+            water_map: &water_map.image,
+            fg: &self.fg,
+            bg: &self.bg,
+            shine: self.shine.as_ref(),
+            water_map_back: None,
+            next: None,
+        };
+        self.render.render(&textures, self.width, self.height)
+    }
+
+    /// 渲染到终端：columns x rows 个字符
+    ///
+    /// `TerminalMode::Ansi` 用半块字符显示折射后的画面，每个字符为上下两个像素；
+    /// `TerminalMode::Ascii` 只显示水面纹理中的雨滴。
+    ///
+    /// Example:
+    /// ```rust
+    /// print!("{}", effect.render_terminal(80, 24, TerminalMode::Ansi));
+    /// ```
+    pub fn render_terminal(&self, columns: u32, rows: u32, mode: TerminalMode) -> String {
+        let water_map = self.rain_drops.water_map();
+        let opts = self.render.options();
+        match mode {
+            TerminalMode::Ansi => {
+                // 先把水面纹理缩小到输出大小，避免逐点采样时漏掉小雨滴
+                let (w, h) = (columns, rows * 2);
+                let water_map = water_map.image.resize(w, h);
+                let textures = SoftwareTextures {
+                    water_map: &water_map,
+                    fg: &self.fg,
+                    bg: &self.bg,
+                    shine: self.shine.as_ref(),
+                    water_map_back: None,
+                    next: None,
+                };
+                terminal::ansi(&self.render.render(&textures, w, h))
+            }
+            TerminalMode::Ascii => terminal::ascii(
+                &water_map.image,
+                columns,
+                rows,
+                opts.alpha_multiply,
+                opts.alpha_subtract,
+            ),
+        }
+    }
+}
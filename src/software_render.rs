@@ -0,0 +1,368 @@
+use crate::rain_render::{RainRenderOptions, FLASH_BRIGHTNESS};
+use crate::rgba::RgbaImage;
+
+type Vec2 = [f32; 2];
+type Vec4 = [f32; 4];
+
+/// 软件渲染用到的纹理，与 WebGL 纹理单元一一对应
+///
+/// 水面纹理的通道含义与 `RainDrops` 画出的一致：r/g 为折射方向，b 为厚度，a 为透明度。
+pub struct SoftwareTextures<'a> {
+    pub water_map: &'a RgbaImage,
+    pub fg: &'a RgbaImage,
+    pub bg: &'a RgbaImage,
+    /// 雨滴高光（未预乘透明度，采样时按 WebGL 上传的方式预乘），None 时不渲染
+    pub shine: Option<&'a RgbaImage>,
+    /// 双层玻璃中后层玻璃的水面纹理，None 时为单层玻璃
+    pub water_map_back: Option<&'a RgbaImage>,
+    /// 过渡目标的前景/背景纹理，按 `cross_fade` 混合
+    pub next: Option<(&'a RgbaImage, &'a RgbaImage)>,
+}
+
+/// CPU 实现的雨滴折射合成，结果与 `FRAGMENT_SHADER` 一致
+///
+/// 不依赖 WebGL，可用于对比测试和服务端渲染。
+///
+/// Example:
+/// ```rust
+/// let render = SoftwareRender::new(None);
+/// let textures = SoftwareTextures {
+///     water_map: &water_map,
+///     fg: &fg,
+///     bg: &bg,
+///     shine: None,
+///     water_map_back: None,
+///     next: None,
+/// };
+/// let image = render.render(&textures, 640, 320);
+/// ```
+pub struct SoftwareRender {
+    opts: RainRenderOptions,
+    parallax_x: f64,
+    parallax_y: f64,
+    cross_fade: f64,
+    flash: f64,
+    max_red_saturation: f64,
+}
+
+impl SoftwareRender {
+    pub fn new(opts: Option<RainRenderOptions>) -> Self {
+        SoftwareRender {
+            opts: opts.unwrap_or_default(),
+            parallax_x: 0.0,
+            parallax_y: 0.0,
+            cross_fade: 0.0,
+            flash: 0.0,
+            max_red_saturation: 1.0,
+        }
+    }
+
+    pub fn options(&self) -> &RainRenderOptions {
+        &self.opts
+    }
+
+    pub fn set_parallax(&mut self, x: f64, y: f64) {
+        self.parallax_x = x;
+        self.parallax_y = y;
+    }
+
+    /// 设置交叉淡入比例（0.0 ~ 1.0）
+    pub fn set_cross_fade(&mut self, cross_fade: f64) {
+        self.cross_fade = cross_fade.clamp(0.0, 1.0);
+    }
+
+    /// 设置闪电的亮度（0.0 ~ 1.0），提高整体亮度并增强高光
+    ///
+    /// 与 `RainRender::set_flash` 一致；闪电纹理由调用方混入前景/背景纹理。
+    pub fn set_flash(&mut self, flash: f64) {
+        self.flash = flash.clamp(0.0, 1.0);
+    }
+
+    /// 限制画面的红色饱和度 R / (R + G + B)，None 为不限制
+    ///
+    /// 与 `RainRender::set_max_red_saturation` 一致。
+    pub fn set_max_red_saturation(&mut self, value: Option<f64>) {
+        self.max_red_saturation = value.map_or(1.0, |value| value.clamp(0.34, 1.0));
+    }
+
+    /// 渲染 width x height 的图像
+    pub fn render(&self, textures: &SoftwareTextures, width: u32, height: u32) -> RgbaImage {
+        let mut image = RgbaImage::new(width, height);
+        self.render_into(textures, &mut image);
+        image
+    }
+
+    /// 渲染到已有的图像，图像大小即输出分辨率
+    pub fn render_into(&self, textures: &SoftwareTextures, image: &mut RgbaImage) {
+        let frag = Fragment {
+            opts: &self.opts,
+            textures,
+            resolution: [image.width as f32, image.height as f32],
+            parallax: [self.parallax_x as f32, self.parallax_y as f32],
+            cross_fade: self.cross_fade as f32,
+            brightness: (self.opts.brightness * (1.0 + FLASH_BRIGHTNESS * self.flash)) as f32,
+            flash: self.flash as f32,
+            max_red_saturation: self.max_red_saturation as f32,
+        };
+
+        for y in 0..image.height {
+            for x in 0..image.width {
+                // 像素中心，第 0 行在上（与 texCoord() 翻转后的 Y 轴一致）
+                let coord = [
+                    (x as f32 + 0.5) / frag.resolution[0],
+                    (y as f32 + 0.5) / frag.resolution[1],
+                ];
+                let color = frag.shade(coord);
+                image.set_pixel(
+                    x,
+                    y,
+                    [
+                        to_u8(color[0]),
+                        to_u8(color[1]),
+                        to_u8(color[2]),
+                        // 画布没有透明通道
+                        255,
+                    ],
+                );
+            }
+        }
+    }
+}
+
+fn to_u8(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn add(a: Vec2, b: Vec2) -> Vec2 {
+    [a[0] + b[0], a[1] + b[1]]
+}
+
+fn scale(a: Vec2, s: f32) -> Vec2 {
+    [a[0] * s, a[1] * s]
+}
+
+fn mul(a: Vec2, b: Vec2) -> Vec2 {
+    [a[0] * b[0], a[1] * b[1]]
+}
+
+fn mix(a: Vec4, b: Vec4, t: f32) -> Vec4 {
+    [0, 1, 2, 3].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+// alpha-blends two colors
+fn blend(bg: Vec4, fg: Vec4) -> Vec4 {
+    let ia = 1.0 - fg[3];
+    let a = fg[3] + bg[3] * ia;
+    if a == 0.0 {
+        return [0.0, 0.0, 0.0, a];
+    }
+    let c = |i: usize| (fg[i] * fg[3] + bg[i] * bg[3] * ia) / a;
+    [c(0), c(1), c(2), a]
+}
+
+fn sample(image: &RgbaImage, pos: Vec2) -> Vec4 {
+    image.sample(pos[0], pos[1])
+}
+
+// moves saturated reds towards grey, keeping the brightness
+fn limit_red(c: Vec4, max_red_saturation: f32) -> Vec4 {
+    let sum = c[0] + c[1] + c[2];
+    let max_red = max_red_saturation * sum;
+    if c[0] <= max_red {
+        return c;
+    }
+    let grey = sum / 3.0;
+    let t = (c[0] - max_red) / (c[0] - grey);
+    [
+        c[0] + (grey - c[0]) * t,
+        c[1] + (grey - c[1]) * t,
+        c[2] + (grey - c[2]) * t,
+        c[3],
+    ]
+}
+
+// textures are uploaded or drawn with premultiplied alpha
+fn unpremultiply(c: Vec4) -> Vec4 {
+    if c[3] > 0.0 {
+        [c[0] / c[3], c[1] / c[3], c[2] / c[3], c[3]]
+    } else {
+        c
+    }
+}
+
+// 单个像素的着色，各方法与着色器中的同名函数对应
+struct Fragment<'a> {
+    opts: &'a RainRenderOptions,
+    textures: &'a SoftwareTextures<'a>,
+    resolution: Vec2,
+    parallax: Vec2,
+    cross_fade: f32,
+    brightness: f32,
+    flash: f32,
+    max_red_saturation: f32,
+}
+
+impl<'a> Fragment<'a> {
+    fn texture_fg(&self, pos: Vec2) -> Vec4 {
+        let cur = sample(self.textures.fg, pos);
+        match self.textures.next {
+            Some((next_fg, _)) => mix(cur, sample(next_fg, pos), self.cross_fade),
+            None => cur,
+        }
+    }
+
+    fn texture_bg(&self, pos: Vec2) -> Vec4 {
+        let cur = sample(self.textures.bg, pos);
+        match self.textures.next {
+            Some((_, next_bg)) => mix(cur, sample(next_bg, pos), self.cross_fade),
+            None => cur,
+        }
+    }
+
+    fn pixel(&self) -> Vec2 {
+        [1.0 / self.resolution[0], 1.0 / self.resolution[1]]
+    }
+
+    fn parallax(&self, v: f64) -> Vec2 {
+        scale(mul(self.parallax, self.pixel()), v as f32)
+    }
+
+    fn alpha(&self, a: f32) -> f32 {
+        (a * self.opts.alpha_multiply as f32 - self.opts.alpha_subtract as f32).clamp(0.0, 1.0)
+    }
+
+    // 折射偏移（单位：纹理坐标）
+    fn refraction_offset(&self, refraction: Vec2, d: f32) -> Vec2 {
+        let min = self.opts.min_refraction as f32;
+        let delta = (self.opts.max_refraction - self.opts.min_refraction) as f32;
+        scale(mul(self.pixel(), refraction), min + d * delta)
+    }
+
+    // scales the bg up and proportionally to fill the container
+    fn scaled_tex_coord(&self, coord: Vec2) -> Vec2 {
+        let bg = self.textures.bg;
+        let texture_ratio = bg.width as f32 / bg.height.max(1) as f32;
+        let ratio = self.resolution[0] / self.resolution[1];
+        let ratio_delta = ratio - texture_ratio;
+        let (scale, offset) = if ratio_delta >= 0.0 {
+            ([1.0, 1.0 + ratio_delta], [0.0, ratio_delta / 2.0])
+        } else {
+            ([1.0 - ratio_delta, 1.0], [-ratio_delta / 2.0, 0.0])
+        };
+        let coord = add(coord, offset);
+        [coord[0] / scale[0], coord[1] / scale[1]]
+    }
+
+    // position on a water map that moves with the given parallax
+    fn pane_coord(&self, coord: Vec2, parallax_pane: f64) -> Vec2 {
+        let p2 = parallax_pane as f32 * 2.0;
+        let scale = [
+            (self.resolution[0] + p2) / self.resolution[0],
+            (self.resolution[1] + p2) / self.resolution[1],
+        ];
+        let scaled = [coord[0] / scale[0], coord[1] / scale[1]];
+        let offset = [(1.0 - 1.0 / scale[0]) / 2.0, (1.0 - 1.0 / scale[1]) / 2.0];
+        add(add(scaled, offset), self.parallax(parallax_pane))
+    }
+
+    fn fg_color(&self, coord: Vec2, x: f32, y: f32) -> Vec4 {
+        let pos = add(
+            self.pane_coord(coord, self.opts.parallax_fg),
+            mul(self.pixel(), [x, y]),
+        );
+        unpremultiply(self.textures.water_map.sample_premultiplied(pos[0], pos[1]))
+    }
+
+    // composites the back pane's drops at coord over what lies behind them
+    fn back_pane(&self, coord: Vec2, base: Vec4, brightness: f32) -> Vec4 {
+        let water_map = match self.textures.water_map_back {
+            Some(water_map) => water_map,
+            None => return base,
+        };
+
+        let pos = self.pane_coord(coord, self.opts.parallax_back);
+        let cur = unpremultiply(water_map.sample_premultiplied(pos[0], pos[1]));
+        let d = cur[2];
+        let a = self.alpha(cur[3]);
+
+        let refraction = [(cur[1] - 0.5) * 2.0, (cur[0] - 0.5) * 2.0];
+        let refraction_pos = add(
+            add(
+                self.scaled_tex_coord(coord),
+                self.refraction_offset(refraction, d),
+            ),
+            self.parallax(self.opts.parallax_bg - self.opts.parallax_back),
+        );
+
+        let tex = self.texture_fg(refraction_pos);
+        blend(
+            base,
+            [
+                tex[0] * brightness,
+                tex[1] * brightness,
+                tex[2] * brightness,
+                a,
+            ],
+        )
+    }
+
+    fn shade(&self, coord: Vec2) -> Vec4 {
+        let brightness = self.brightness;
+
+        let bg = self.texture_bg(add(
+            self.scaled_tex_coord(coord),
+            self.parallax(self.opts.parallax_bg),
+        ));
+        let bg = self.back_pane(coord, bg, brightness);
+
+        let cur = self.fg_color(coord, 0.0, 0.0);
+
+        // "thickness"
+        let d = cur[2];
+        let a = self.alpha(cur[3]);
+
+        let refraction = [(cur[1] - 0.5) * 2.0, (cur[0] - 0.5) * 2.0];
+        let offset = self.refraction_offset(refraction, d);
+        let refraction_pos = add(
+            add(self.scaled_tex_coord(coord), offset),
+            self.parallax(self.opts.parallax_bg - self.opts.parallax_fg),
+        );
+
+        let tex = self.texture_fg(refraction_pos);
+        // the back pane seen through the front pane's drops
+        let mut tex = self.back_pane(add(coord, offset), tex, 1.0);
+
+        if let Some(shine) = self.textures.shine {
+            let max_shine = 490.0;
+            let min_shine = max_shine * 0.18;
+            let shine_pos = add(
+                [0.5, 0.5],
+                scale(
+                    scale(refraction, 1.0 / 512.0),
+                    -(min_shine + (max_shine - min_shine) * d),
+                ),
+            );
+            let mut shine = unpremultiply(shine.sample_premultiplied(shine_pos[0], shine_pos[1]));
+            shine[3] = (shine[3] * (1.0 + self.flash * 2.0)).min(1.0);
+            tex = blend(tex, shine);
+        }
+
+        let mut fg = [
+            tex[0] * brightness,
+            tex[1] * brightness,
+            tex[2] * brightness,
+            a,
+        ];
+
+        if self.opts.render_shadow {
+            let border_alpha = self.fg_color(coord, 0.0, -(d * 6.0))[3];
+            let border_alpha = (border_alpha * self.opts.alpha_multiply as f32
+                - (self.opts.alpha_subtract as f32 + 0.5))
+                .clamp(0.0, 1.0)
+                * 0.2;
+            fg = blend([0.0, 0.0, 0.0, border_alpha], fg);
+        }
+
+        limit_red(blend(bg, fg), self.max_red_saturation)
+    }
+}
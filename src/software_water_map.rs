@@ -0,0 +1,196 @@
+use crate::rgba::RgbaImage;
+use crate::water_map::{WaterMap, DROP_SIZE};
+use std::rc::Rc;
+
+/// 雨滴精灵（软件描画用）
+///
+/// 与 `CanvasSprites` 的合成方式一致：颜色图叠加 screen 蓝色表示厚度，透明度取自透明度图。
+/// 厚度在描画时才叠加，所以只保存一张底图。
+///
+/// Example:
+/// ```rust
+/// let sprites = Rc::new(DropSprites::new(&drop_alpha, &drop_color));
+/// ```
+pub struct DropSprites {
+    // 颜色图，a 为颜色图自身的透明度
+    color: RgbaImage,
+    // 透明度图
+    alpha: RgbaImage,
+}
+
+impl DropSprites {
+    pub fn new(alpha: &RgbaImage, color: &RgbaImage) -> Self {
+        DropSprites {
+            color: color.resize(DROP_SIZE, DROP_SIZE),
+            alpha: alpha.resize(DROP_SIZE, DROP_SIZE),
+        }
+    }
+
+    /// 厚度为 depth（0 ~ 254）的精灵在 (u, v) 处的颜色（0.0 ~ 1.0）
+    fn sample(&self, depth: f32, u: f32, v: f32) -> [f32; 4] {
+        let [r, g, b, a] = self.color.sample(u, v);
+        let alpha = self.alpha.sample(u, v)[3];
+        // screen 混合：颜色图透明的部分只剩下蓝色
+        let screen = 1.0 - (1.0 - b) * (1.0 - depth);
+        [r * a, g * a, (1.0 - a) * depth + a * screen, alpha]
+    }
+}
+
+/// 在内存中描画的水面纹理，不依赖浏览器
+///
+/// 像素保存为未预乘的 RGBA，合成规则与 Canvas2D 相同。
+pub struct SoftwareWaterMap {
+    pub image: RgbaImage,
+    sprites: Rc<DropSprites>,
+}
+
+impl SoftwareWaterMap {
+    pub fn new(w: u32, h: u32, sprites: Rc<DropSprites>) -> Self {
+        SoftwareWaterMap {
+            image: RgbaImage::new(w, h),
+            sprites,
+        }
+    }
+
+    // 矩形覆盖的像素范围（裁剪到纹理内）
+    fn bounds(&self, x: f64, y: f64, w: f64, h: f64) -> Option<(u32, u32, u32, u32)> {
+        let x0 = x.floor().max(0.0);
+        let y0 = y.floor().max(0.0);
+        let x1 = (x + w).ceil().min(self.image.width as f64);
+        let y1 = (y + h).ceil().min(self.image.height as f64);
+        if x0 >= x1 || y0 >= y1 {
+            return None;
+        }
+        Some((x0 as u32, y0 as u32, x1 as u32, y1 as u32))
+    }
+
+    // source-over 合成一个像素
+    fn blend(&mut self, x: u32, y: u32, [r, g, b, a]: [f32; 4]) {
+        if a <= 0.0 {
+            return;
+        }
+        let dst = self.image.pixel(x, y).map(|c| c as f32 / 255.0);
+        let ia = 1.0 - a;
+        let out = a + dst[3] * ia;
+        let c = |s: f32, d: f32| (s * a + d * dst[3] * ia) / out;
+        let color = [c(r, dst[0]), c(g, dst[1]), c(b, dst[2]), out];
+        self.image.set_pixel(
+            x,
+            y,
+            color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8),
+        );
+    }
+
+    // destination-out：按覆盖率擦除一个像素
+    fn erase_pixel(&mut self, x: u32, y: u32, coverage: f32) {
+        if coverage <= 0.0 {
+            return;
+        }
+        let mut pixel = self.image.pixel(x, y);
+        pixel[3] = (pixel[3] as f32 * (1.0 - coverage.min(1.0))).round() as u8;
+        self.image.set_pixel(x, y, pixel);
+    }
+}
+
+impl WaterMap for SoftwareWaterMap {
+    fn clear(&mut self) {
+        self.image.clear();
+    }
+
+    fn draw_drop(&mut self, depth: f64, x: f64, y: f64, w: f64, h: f64) {
+        let (x0, y0, x1, y1) = match self.bounds(x, y, w, h) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let depth = (depth * 254.0).floor() as f32 / 255.0;
+        let sprites = self.sprites.clone();
+        for py in y0..y1 {
+            let v = ((py as f64 + 0.5 - y) / h) as f32;
+            for px in x0..x1 {
+                let u = ((px as f64 + 0.5 - x) / w) as f32;
+                if !(0.0..=1.0).contains(&u) || !(0.0..=1.0).contains(&v) {
+                    continue;
+                }
+                self.blend(px, py, sprites.sample(depth, u, v));
+            }
+        }
+    }
+
+    fn erase(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        let (x0, y0, x1, y1) = match self.bounds(x, y, w, h) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let (cx, cy) = (x + w * 0.5, y + h * 0.5);
+        let (rx, ry) = (w * 0.5, h * 0.5);
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let dx = (px as f64 + 0.5 - cx) / rx;
+                let dy = (py as f64 + 0.5 - cy) / ry;
+                // 边缘 1 像素内抗锯齿
+                let d = ((dx * dx + dy * dy).sqrt() - 1.0) * rx.min(ry);
+                self.erase_pixel(px, py, (0.5 - d).clamp(0.0, 1.0) as f32);
+            }
+        }
+    }
+
+    fn fade(&mut self, alpha: f64) {
+        let keep = 1.0 - alpha.clamp(0.0, 1.0);
+        for a in self.image.data.iter_mut().skip(3).step_by(4) {
+            *a = (*a as f64 * keep).round() as u8;
+        }
+    }
+
+    fn draw_streak(&mut self, from: (f64, f64), to: (f64, f64), width: f64, depth: u8) {
+        let r = width * 0.5;
+        let bounds = self.bounds(
+            from.0.min(to.0) - r,
+            from.1.min(to.1) - r,
+            (from.0 - to.0).abs() + width,
+            (from.1 - to.1).abs() + width,
+        );
+        let (x0, y0, x1, y1) = match bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = dx * dx + dy * dy;
+        let color = [0.5, 0.5, depth as f32 / 255.0];
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let (qx, qy) = (px as f64 + 0.5 - from.0, py as f64 + 0.5 - from.1);
+                // 到线段的距离（圆头）
+                let t = if length > 0.0 {
+                    ((qx * dx + qy * dy) / length).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                let d = ((qx - dx * t).powi(2) + (qy - dy * t).powi(2)).sqrt() - r;
+                let coverage = (0.5 - d).clamp(0.0, 1.0) as f32;
+                self.blend(px, py, [color[0], color[1], color[2], 0.75 * coverage]);
+            }
+        }
+    }
+
+    fn draw_layer(&mut self, layer: &Self) {
+        let (w, h) = (self.image.width, self.image.height);
+        for y in 0..h {
+            for x in 0..w {
+                let color = if layer.image.width == w && layer.image.height == h {
+                    layer.image.pixel(x, y).map(|c| c as f32 / 255.0)
+                } else {
+                    layer
+                        .image
+                        .sample((x as f32 + 0.5) / w as f32, (y as f32 + 0.5) / h as f32)
+                };
+                self.blend(x, y, color);
+            }
+        }
+    }
+
+    fn resize(&mut self, w: u32, h: u32) {
+        if (self.image.width, self.image.height) != (w, h) {
+            self.image = self.image.resize(w, h);
+        }
+    }
+}
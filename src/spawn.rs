@@ -0,0 +1,240 @@
+use crate::create_canvas_element;
+use js_sys::{Array, Function};
+use rand::{Rng, RngCore};
+use std::f64::consts::PI;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlImageElement;
+
+/// 雨点生成区域（单位：未缩放的像素）
+pub struct SpawnArea {
+    pub width: f64,
+    pub height: f64,
+    /// 雨点在Y轴生成范围
+    ///
+    /// Example:
+    /// ```rust
+    /// let (min, max) = y_range;
+    /// ```
+    pub y_range: (f64, f64),
+}
+
+/// 新生成的雨点
+pub struct Spawn {
+    pub x: f64,
+    pub y: f64,
+    /// 半径比例（0.0 ~ 1.0，映射到雨点半径范围）
+    pub size: f64,
+}
+
+/// 雨点生成策略
+///
+/// 决定新雨点出现的位置和大小，阵风等调制在策略之后叠加。
+///
+/// Example:
+/// ```rust
+/// struct Center;
+///
+/// impl SpawnStrategy for Center {
+///     fn spawn(&mut self, area: &SpawnArea, rng: &mut dyn RngCore) -> Spawn {
+///         Spawn {
+///             x: area.width * 0.5,
+///             y: area.height * 0.5,
+///             size: rng.gen::<f64>(),
+///         }
+///     }
+/// }
+/// ```
+pub trait SpawnStrategy {
+    fn spawn(&mut self, area: &SpawnArea, rng: &mut dyn RngCore) -> Spawn;
+}
+
+/// 均匀分布：X 轴均匀，Y 轴在生成范围内均匀，小雨点居多
+#[derive(Default)]
+pub struct UniformSpawn;
+
+impl SpawnStrategy for UniformSpawn {
+    fn spawn(&mut self, area: &SpawnArea, rng: &mut dyn RngCore) -> Spawn {
+        let (y_min, y_max) = area.y_range;
+        Spawn {
+            x: rng.gen::<f64>() * area.width,
+            y: y_min + rng.gen::<f64>() * (y_max - y_min),
+            size: rng.gen::<f64>().powi(3),
+        }
+    }
+}
+
+/// 成簇分布：雨点聚集在若干个缓慢漂移的中心附近
+pub struct ClusteredSpawn {
+    /// 簇的数量
+    pub clusters: usize,
+    /// 簇的半径（相对区域宽度）
+    pub spread: f64,
+    /// 每次生成时一个簇移动到新位置的几率
+    pub drift: f64,
+    // 簇中心（相对坐标）
+    centers: Vec<(f64, f64)>,
+}
+
+impl Default for ClusteredSpawn {
+    fn default() -> Self {
+        ClusteredSpawn {
+            clusters: 5,
+            spread: 0.06,
+            drift: 0.02,
+            centers: Vec::new(),
+        }
+    }
+}
+
+impl ClusteredSpawn {
+    pub fn new() -> Self {
+        ClusteredSpawn::default()
+    }
+}
+
+impl SpawnStrategy for ClusteredSpawn {
+    fn spawn(&mut self, area: &SpawnArea, rng: &mut dyn RngCore) -> Spawn {
+        let clusters = self.clusters.max(1);
+        if self.centers.len() != clusters {
+            self.centers = (0..clusters)
+                .map(|_| (rng.gen::<f64>(), rng.gen::<f64>()))
+                .collect();
+        }
+        if rng.gen::<f64>() < self.drift {
+            let i = rng.gen_range(0..clusters);
+            self.centers[i] = (rng.gen::<f64>(), rng.gen::<f64>());
+        }
+
+        let (cx, cy) = self.centers[rng.gen_range(0..clusters)];
+        let (y_min, y_max) = area.y_range;
+
+        // Box-Muller 正态分布
+        let radius = (-2.0 * (1.0 - rng.gen::<f64>()).ln()).sqrt() * self.spread * area.width;
+        let angle = rng.gen::<f64>() * PI * 2.0;
+
+        Spawn {
+            x: (cx * area.width + radius * angle.cos()).clamp(0.0, area.width),
+            y: (y_min + cy * (y_max - y_min) + radius * angle.sin()).clamp(y_min, y_max),
+            size: rng.gen::<f64>().powi(3),
+        }
+    }
+}
+
+/// 窗框滴水：雨点从窗框上沿流下，水滴偏大
+pub struct EdgeSpawn {
+    /// 滴水区域的高度（相对区域高度）
+    pub depth: f64,
+}
+
+impl Default for EdgeSpawn {
+    fn default() -> Self {
+        EdgeSpawn { depth: 0.05 }
+    }
+}
+
+impl EdgeSpawn {
+    pub fn new() -> Self {
+        EdgeSpawn::default()
+    }
+}
+
+impl SpawnStrategy for EdgeSpawn {
+    fn spawn(&mut self, area: &SpawnArea, rng: &mut dyn RngCore) -> Spawn {
+        Spawn {
+            x: rng.gen::<f64>() * area.width,
+            y: rng.gen::<f64>() * self.depth * area.height,
+            size: rng.gen::<f64>().powf(1.5),
+        }
+    }
+}
+
+/// 密度图：按图片亮度分布，越亮的位置雨点越多
+///
+/// 密度图覆盖整个区域，不受 `y_range` 限制。
+pub struct DensitySpawn {
+    width: usize,
+    height: usize,
+    // 累积分布
+    cdf: Vec<f64>,
+}
+
+impl DensitySpawn {
+    /// 由 RGBA 像素创建
+    pub fn new(width: usize, height: usize, rgba: &[u8]) -> Self {
+        let mut total = 0.0;
+        let cdf = rgba
+            .chunks_exact(4)
+            .take(width * height)
+            .map(|p| {
+                let luma = 0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64;
+                total += luma * p[3] as f64 / (255.0 * 255.0);
+                total
+            })
+            .collect();
+
+        DensitySpawn { width, height, cdf }
+    }
+
+    /// 由图片创建，图片先缩小到 w x h 再采样
+    pub fn from_image(image: &HtmlImageElement, w: u32, h: u32) -> Result<Self, JsValue> {
+        let (_canvas, ctx) = create_canvas_element(w, h)?;
+        ctx.draw_image_with_html_image_element_and_dw_and_dh(image, 0.0, 0.0, w as f64, h as f64)?;
+        let data = ctx.get_image_data(0.0, 0.0, w as f64, h as f64)?.data();
+
+        Ok(DensitySpawn::new(w as usize, h as usize, &data))
+    }
+}
+
+impl SpawnStrategy for DensitySpawn {
+    fn spawn(&mut self, area: &SpawnArea, rng: &mut dyn RngCore) -> Spawn {
+        let total = self.cdf.last().copied().unwrap_or(0.0);
+        if total <= 0.0 {
+            return UniformSpawn.spawn(area, rng);
+        }
+
+        let u = rng.gen::<f64>() * total;
+        let i = self.cdf.partition_point(|&c| c < u).min(self.cdf.len() - 1);
+        let (cx, cy) = ((i % self.width) as f64, (i / self.width) as f64);
+
+        Spawn {
+            x: (cx + rng.gen::<f64>()) / self.width as f64 * area.width,
+            y: (cy + rng.gen::<f64>()) / self.height as f64 * area.height,
+            size: rng.gen::<f64>().powi(3),
+        }
+    }
+}
+
+/// 由 JavaScript 函数生成雨点
+///
+/// Example:
+/// ```javascript
+/// effect.set_spawn_fn((width, height) => [Math.random() * width, 0, Math.random()]);
+/// ```
+pub struct FunctionSpawn {
+    f: Function,
+}
+
+impl FunctionSpawn {
+    pub fn new(f: Function) -> Self {
+        FunctionSpawn { f }
+    }
+}
+
+impl SpawnStrategy for FunctionSpawn {
+    fn spawn(&mut self, area: &SpawnArea, rng: &mut dyn RngCore) -> Spawn {
+        let value = self.f.call2(
+            &JsValue::NULL,
+            &JsValue::from(area.width),
+            &JsValue::from(area.height),
+        );
+        // 返回值不是数组（如 undefined）时退回均匀分布
+        match value.ok().and_then(|value| value.dyn_into::<Array>().ok()) {
+            Some(value) if value.length() >= 3 => Spawn {
+                x: value.get(0).as_f64().unwrap_or(0.0),
+                y: value.get(1).as_f64().unwrap_or(0.0),
+                size: value.get(2).as_f64().unwrap_or(0.0).clamp(0.0, 1.0),
+            },
+            _ => UniformSpawn.spawn(area, rng),
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::rgba::RgbaImage;
+use std::fmt::Write;
+
+/// 终端输出方式
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TerminalMode {
+    /// 24 位色 + 半块字符（▀），每个字符显示上下两个像素
+    Ansi,
+    /// 纯文本，按雨滴厚度选择字符，适合没有颜色的日志
+    Ascii,
+}
+
+// 由薄到厚的雨滴字符
+const ASCII_DROPS: [char; 4] = ['.', 'o', 'O', '@'];
+
+/// 把图片画成 ANSI 24 位色文本：每个字符的前景色为上方像素，背景色为下方像素
+///
+/// 图片高度为奇数时，最后一行的下半部分使用终端默认背景。
+///
+/// Example:
+/// ```rust
+/// let frame = effect.render_size(80, 48);
+/// print!("{}", ansi(&frame));
+/// ```
+pub fn ansi(image: &RgbaImage) -> String {
+    let mut out = String::new();
+    for y in (0..image.height).step_by(2) {
+        // 只在颜色变化时输出转义序列
+        let mut last: Option<([u8; 4], Option<[u8; 4]>)> = None;
+        for x in 0..image.width {
+            let top = image.pixel(x, y);
+            let bottom = (y + 1 < image.height).then(|| image.pixel(x, y + 1));
+            if last != Some((top, bottom)) {
+                let [r, g, b, _] = top;
+                write!(out, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+                match bottom {
+                    Some([r, g, b, _]) => write!(out, "\x1b[48;2;{};{};{}m", r, g, b).unwrap(),
+                    None => out.push_str("\x1b[49m"),
+                }
+                last = Some((top, bottom));
+            }
+            out.push('▀');
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// 把水面纹理画成纯文本：没有雨滴为空格，雨滴按厚度显示为 `.oO@`
+///
+/// 水面纹理先缩小到 columns x rows，透明度按 alpha_multiply / alpha_subtract 处理，
+/// 与着色器中看到的雨滴范围一致。
+///
+/// Example:
+/// ```rust
+/// print!("{}", ascii(&water_map, 80, 24, 6.0, 3.0));
+/// ```
+pub fn ascii(
+    water_map: &RgbaImage,
+    columns: u32,
+    rows: u32,
+    alpha_multiply: f64,
+    alpha_subtract: f64,
+) -> String {
+    let cells = water_map.resize(columns, rows);
+    let mut out = String::new();
+    for y in 0..cells.height {
+        let line: String = (0..cells.width)
+            .map(|x| {
+                let [_, _, depth, alpha] = cells.pixel(x, y);
+                let a = alpha as f64 / 255.0 * alpha_multiply - alpha_subtract;
+                if a <= 0.1 {
+                    return ' ';
+                }
+                let i = (depth as usize * ASCII_DROPS.len() / 256).min(ASCII_DROPS.len() - 1);
+                ASCII_DROPS[i]
+            })
+            .collect();
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
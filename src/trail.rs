@@ -0,0 +1,136 @@
+use crate::drop::Drop;
+use rand::Rng;
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+/// 雨迹样式
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrailMode {
+    /// 下滑时留下一串小雨滴
+    Drops,
+    /// 下滑时留下连续的水痕，随时间变细并断裂成水珠
+    Streaks,
+}
+
+pub struct TrailPoint {
+    pub x: f64,
+    pub y: f64,
+    /// 水痕半宽
+    pub r: f64,
+    /// 存在时间（帧）
+    pub age: f64,
+}
+
+/// 下滑雨滴留下的连续水痕（折线）
+///
+/// Example:
+/// ```rust
+/// let mut trail = Trail::new(&rc_drop);
+/// trail.push(drop.x, drop.y, drop.r * 0.3);
+/// trail.update(time_scale);
+/// let beads = trail.take_beads(bead_age, &mut rng);
+/// ```
+pub struct Trail {
+    pub points: Vec<TrailPoint>,
+    // 留下水痕的雨滴
+    owner: Weak<RefCell<Drop>>,
+    // 距离上一次延长的时间（帧）
+    idle: f64,
+    // 到下一颗水珠的距离
+    next_bead: f64,
+    // 已从雨迹列表中移除
+    closed: bool,
+}
+
+impl Trail {
+    pub fn new(owner: &Rc<RefCell<Drop>>) -> Self {
+        Trail {
+            points: Vec::new(),
+            owner: Rc::downgrade(owner),
+            idle: 0.0,
+            next_bead: 0.0,
+            closed: false,
+        }
+    }
+
+    /// 留下水痕的雨滴（已被回收时为 None）
+    pub fn owner(&self) -> Option<Rc<RefCell<Drop>>> {
+        self.owner.upgrade()
+    }
+
+    /// 延长水痕，离上一个点太近时只更新末端
+    pub fn push(&mut self, x: f64, y: f64, r: f64) {
+        self.idle = 0.0;
+        if let Some(last) = self.points.last_mut() {
+            let d = ((x - last.x).powi(2) + (y - last.y).powi(2)).sqrt();
+            if d < (r * 0.5).max(2.0) {
+                last.r = last.r.max(r);
+                return;
+            }
+        }
+        self.points.push(TrailPoint { x, y, r, age: 0.0 });
+    }
+
+    pub fn update(&mut self, time_scale: f64) {
+        self.idle += time_scale;
+        for point in self.points.iter_mut() {
+            point.age += time_scale;
+        }
+    }
+
+    /// 水痕宽度比例：随时间由 1.0 变细到 0.4
+    pub fn narrowing(point: &TrailPoint, bead_age: f64) -> f64 {
+        1.0 - 0.6 * (point.age / bead_age).clamp(0.0, 1.0)
+    }
+
+    /// 取出超过 bead_age 的一段水痕，沿折线断裂成水珠，返回 (x, y, r)
+    pub fn take_beads<R: Rng>(&mut self, bead_age: f64, rng: &mut R) -> Vec<(f64, f64, f64)> {
+        let count = self.points.iter().take_while(|p| p.age > bead_age).count();
+        // 保留最后一个点作为剩余水痕的起点
+        let count = if count == self.points.len() && self.idle <= bead_age {
+            count.saturating_sub(1)
+        } else {
+            count
+        };
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let mut beads = Vec::new();
+        let removed: Vec<TrailPoint> = self.points.drain(..count).collect();
+        let mut segments: Vec<(&TrailPoint, &TrailPoint)> =
+            removed.windows(2).map(|w| (&w[0], &w[1])).collect();
+        if let (Some(last), Some(next)) = (removed.last(), self.points.first()) {
+            segments.push((last, next));
+        }
+
+        for (a, b) in segments {
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            let length = (dx.powi(2) + dy.powi(2)).sqrt();
+            let mut t = self.next_bead;
+            while t < length {
+                let k = t / length;
+                let r = (a.r + (b.r - a.r) * k) * (0.8 + rng.gen::<f64>() * 0.6);
+                beads.push((a.x + dx * k, a.y + dy * k, r));
+                // 半径为 0 时也至少前进 1 像素
+                t += (r * (3.0 + rng.gen::<f64>() * 4.0)).max(1.0);
+            }
+            self.next_bead = t - length;
+        }
+
+        beads
+    }
+
+    /// 水痕已经完全断裂，且不再延长
+    pub fn is_finished(&self, bead_age: f64) -> bool {
+        self.points.len() < 2 && self.idle > bead_age
+    }
+
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
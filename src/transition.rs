@@ -0,0 +1,67 @@
+use crate::images::WeatherImage;
+use crate::weather::{Weather, WeatherOptions};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// 天气过渡
+///
+/// 在 `duration` 毫秒内把天气参数从 `from` 插值到目标天气，
+/// 同时驱动前景/背景纹理的交叉淡入淡出；image 为过渡目标的图片，结束时一并返回。
+///
+/// Example:
+/// ```rust
+/// let transition = Transition::new(from, weather, image, now(), 3000.0);
+/// let opts = transition.options(now());
+/// ```
+pub struct Transition<I = Rc<RefCell<WeatherImage>>> {
+    from: WeatherOptions,
+    to: Weather,
+    image: I,
+    start: f64,
+    duration: f64,
+}
+
+impl<I> Transition<I> {
+    pub fn new(from: WeatherOptions, to: Weather, image: I, start: f64, duration: f64) -> Self {
+        Transition {
+            from,
+            to,
+            image,
+            start,
+            duration: duration.max(0.0),
+        }
+    }
+
+    /// 过渡目标的天气图片
+    pub fn image(&self) -> &I {
+        &self.image
+    }
+
+    /// 过渡进度（0.0 ~ 1.0），两端缓动
+    pub fn progress(&self, now: f64) -> f64 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        let t = ((now - self.start) / self.duration).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// 推迟过渡（暂停期间不推进）
+    pub fn delay(&mut self, duration: f64) {
+        self.start += duration;
+    }
+
+    pub fn is_finished(&self, now: f64) -> bool {
+        now - self.start >= self.duration
+    }
+
+    /// 当前时刻的天气参数
+    pub fn options(&self, now: f64) -> WeatherOptions {
+        self.from.lerp(self.to.options(), self.progress(now))
+    }
+
+    /// 结束过渡，返回目标天气和图片
+    pub fn finish(self) -> (Weather, I) {
+        (self.to, self.image)
+    }
+}
@@ -0,0 +1,157 @@
+use crate::blur::BlurOptions;
+use crate::textures::Texture;
+use crate::{create_canvas_element, document};
+use js_sys::Promise;
+use std::cell::Cell;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{CanvasRenderingContext2d, HtmlMediaElement, HtmlVideoElement, MediaStream};
+
+/// 作为前景和背景的视频，来自视频地址或 MediaStream（如摄像头）
+///
+/// 视频静音、循环、内联播放；只有出现新的一帧时才需要重画纹理。
+///
+/// Example:
+/// ```rust
+/// let video = VideoSource::from_url("img/street.mp4").await?;
+/// if video.next_frame() {
+///     video.draw(&texture.ctx, 384.0, 256.0);
+/// }
+/// ```
+pub struct VideoSource {
+    video: HtmlVideoElement,
+    // 上一次取用的帧的时刻，负数为还没有取用
+    last_time: Cell<f64>,
+    // 模糊前缩小的帧，每帧重复使用
+    scratch: Texture,
+}
+
+impl VideoSource {
+    pub async fn from_url(src: &str) -> Result<Self, JsValue> {
+        let video = VideoSource::create_video()?;
+        video.set_loop(true);
+        video.set_src(src);
+        VideoSource::start(video).await
+    }
+
+    /// 使用 MediaStream，停止时不会关闭其中的轨道
+    pub async fn from_stream(stream: &MediaStream) -> Result<Self, JsValue> {
+        let video = VideoSource::create_video()?;
+        video.set_src_object(Some(stream));
+        VideoSource::start(video).await
+    }
+
+    fn create_video() -> Result<HtmlVideoElement, JsValue> {
+        let video = document()
+            .create_element("video")?
+            .dyn_into::<HtmlVideoElement>()?;
+        // 静音和内联播放才能不经用户操作自动播放
+        video.set_muted(true);
+        video.set_autoplay(true);
+        video.set_attribute("playsinline", "")?;
+        video.set_cross_origin(Some("anonymous"));
+        Ok(video)
+    }
+
+    // 等到有第一帧后开始播放
+    async fn start(video: HtmlVideoElement) -> Result<Self, JsValue> {
+        if video.ready_state() < HtmlMediaElement::HAVE_CURRENT_DATA {
+            let loaded = Promise::new(&mut |resolve, reject| {
+                video.set_onloadeddata(Some(&resolve));
+                video.set_onerror(Some(&reject));
+            });
+            let result = JsFuture::from(loaded).await;
+            video.set_onloadeddata(None);
+            video.set_onerror(None);
+            result.map_err(|_| JsValue::from("failed to load the video"))?;
+        }
+        JsFuture::from(video.play()?).await?;
+        let (canvas, ctx) = create_canvas_element(1, 1)?;
+
+        Ok(VideoSource {
+            video,
+            last_time: Cell::new(-1.0),
+            scratch: Texture { canvas, ctx },
+        })
+    }
+
+    /// 有还没取用的新一帧时返回 true
+    pub fn next_frame(&self) -> bool {
+        if self.video.ready_state() < HtmlMediaElement::HAVE_CURRENT_DATA {
+            return false;
+        }
+        let time = self.video.current_time();
+        time != self.last_time.replace(time)
+    }
+
+    /// 下一次 `next_frame` 时重画当前帧
+    pub fn redraw(&self) {
+        self.last_time.set(-1.0);
+    }
+
+    /// 把当前帧按 w x h 的比例居中裁切后画满 (0, 0, w, h)
+    pub fn draw(&self, ctx: &CanvasRenderingContext2d, w: f64, h: f64) {
+        let (vw, vh) = (
+            self.video.video_width() as f64,
+            self.video.video_height() as f64,
+        );
+        if vw <= 0.0 || vh <= 0.0 {
+            return;
+        }
+        let scale = (vw / w).min(vh / h);
+        let (sw, sh) = (w * scale, h * scale);
+        ctx.draw_image_with_html_video_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+            &self.video,
+            (vw - sw) / 2.0,
+            (vh - sh) / 2.0,
+            sw,
+            sh,
+            0.0,
+            0.0,
+            w,
+            h,
+        )
+        .unwrap();
+    }
+
+    /// 与 `draw` 相同，按 blur 模糊后画满 (0, 0, w, h)
+    ///
+    /// 先按 quality 缩小画到重复使用的画布上，再用画布的 filter 模糊放大，不读回像素。
+    pub fn draw_blurred(&self, ctx: &CanvasRenderingContext2d, w: f64, h: f64, blur: BlurOptions) {
+        if blur.radius <= 0.0 {
+            self.draw(ctx, w, h);
+            return;
+        }
+
+        let scale = blur.quality.clamp(0.1, 1.0);
+        let (sw, sh) = ((w * scale).ceil() as u32, (h * scale).ceil() as u32);
+        let scratch = &self.scratch;
+        if (scratch.canvas.width(), scratch.canvas.height()) != (sw, sh) {
+            scratch.canvas.set_width(sw);
+            scratch.canvas.set_height(sh);
+        }
+        self.draw(&scratch.ctx, sw as f64, sh as f64);
+
+        // blur() 的参数为 σ（半径约 3σ）；向外多画出半径的范围，边缘不会模糊成透明
+        let margin = blur.radius;
+        ctx.set_filter(&format!("blur({}px)", blur.radius / 3.0));
+        ctx.draw_image_with_html_canvas_element_and_dw_and_dh(
+            &scratch.canvas,
+            -margin,
+            -margin,
+            w + margin * 2.0,
+            h + margin * 2.0,
+        )
+        .unwrap();
+        ctx.set_filter("none");
+    }
+}
+
+impl Drop for VideoSource {
+    fn drop(&mut self) {
+        let _ = self.video.pause();
+        self.video.set_src_object(None);
+        let _ = self.video.remove_attribute("src");
+    }
+}
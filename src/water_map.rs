@@ -0,0 +1,389 @@
+use crate::create_canvas_element;
+use crate::gpu_water_map::{GpuWaterMap, RenderTarget};
+use crate::images::ColorImage;
+use crate::textures::Texture;
+use std::cell::RefCell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+use web_sys::HtmlCanvasElement;
+
+/// 雨滴精灵大小
+pub const DROP_SIZE: u32 = 64;
+
+/// 水面纹理：雨滴模拟描画的目标
+///
+/// 像素的 r/g 为折射方向，b 为厚度，a 为透明度，由 `FRAGMENT_SHADER` 合成到画面上。
+/// 坐标单位为纹理像素。
+///
+/// Example:
+/// ```rust
+/// water_map.clear();
+/// water_map.draw_drop(0.5, x, y, w, h);
+/// water_map.erase(x, y, w, h);
+/// ```
+pub trait WaterMap {
+    /// 清空纹理
+    fn clear(&mut self);
+
+    /// 把雨滴精灵画到矩形 (x, y, w, h) 中，depth 为厚度（0.0 ~ 1.0）
+    fn draw_drop(&mut self, depth: f64, x: f64, y: f64, w: f64, h: f64);
+
+    /// 擦除矩形 (x, y, w, h) 内切的椭圆
+    fn erase(&mut self, x: f64, y: f64, w: f64, h: f64);
+
+    /// 整体淡出（alpha: 每次擦除的比例）
+    fn fade(&mut self, alpha: f64);
+
+    /// 画一段圆头水痕，depth 为厚度（0 ~ 255）
+    fn draw_streak(&mut self, from: (f64, f64), to: (f64, f64), width: f64, depth: u8);
+
+    /// 把另一张纹理拉伸覆盖到整张纹理上
+    fn draw_layer(&mut self, layer: &Self);
+
+    /// 调整纹理大小，已有的内容拉伸到新的大小
+    fn resize(&mut self, w: u32, h: u32);
+
+    /// 提交排队的描画（描画不是立即完成的实现需要），每帧结束时调用
+    fn flush(&mut self) {}
+}
+
+/// 预先描画的雨滴精灵（按厚度 0 ~ 254 各一张）
+pub struct CanvasSprites {
+    color_image: Rc<ColorImage>,
+    drops: Vec<HtmlCanvasElement>,
+    clear: Option<HtmlCanvasElement>,
+}
+
+impl CanvasSprites {
+    pub fn new(color_image: Rc<ColorImage>) -> Self {
+        CanvasSprites {
+            color_image,
+            drops: Vec::new(),
+            clear: None,
+        }
+    }
+
+    /// 描画全部精灵，已经描画过时不再描画
+    pub fn render(&mut self) -> Result<(), JsValue> {
+        if !self.drops.is_empty() {
+            return Ok(());
+        }
+        let (buf, buf_ctx) = create_canvas_element(DROP_SIZE, DROP_SIZE)?;
+
+        let values = (0..255).collect::<Vec<_>>();
+        self.drops = values
+            .iter()
+            .map(|i| {
+                let (drop, drop_ctx) = create_canvas_element(DROP_SIZE, DROP_SIZE).unwrap();
+
+                buf_ctx.clear_rect(0.0, 0.0, DROP_SIZE as f64, DROP_SIZE as f64);
+
+                // 颜色
+                buf_ctx
+                    .set_global_composite_operation("source-over")
+                    .unwrap();
+                buf_ctx
+                    .draw_image_with_html_image_element_and_dw_and_dh(
+                        &self.color_image.color,
+                        0.0,
+                        0.0,
+                        DROP_SIZE as f64,
+                        DROP_SIZE as f64,
+                    )
+                    .unwrap();
+
+                // blue overlay, for depth
+                buf_ctx.set_global_composite_operation("screen").unwrap();
+                buf_ctx.set_fill_style(&JsValue::from(format!("rgba(0,0,{},1)", i)));
+                buf_ctx.fill_rect(0.0, 0.0, DROP_SIZE as f64, DROP_SIZE as f64);
+
+                // alpha
+                drop_ctx
+                    .set_global_composite_operation("source-over")
+                    .unwrap();
+                drop_ctx
+                    .draw_image_with_html_image_element_and_dw_and_dh(
+                        &self.color_image.alpha,
+                        0.0,
+                        0.0,
+                        DROP_SIZE as f64,
+                        DROP_SIZE as f64,
+                    )
+                    .unwrap();
+
+                // color
+                drop_ctx
+                    .set_global_composite_operation("source-in")
+                    .unwrap();
+                drop_ctx
+                    .draw_image_with_html_canvas_element_and_dw_and_dh(
+                        &buf,
+                        0.0,
+                        0.0,
+                        DROP_SIZE as f64,
+                        DROP_SIZE as f64,
+                    )
+                    .unwrap();
+                drop
+            })
+            .collect::<Vec<HtmlCanvasElement>>();
+
+        let (clear, clear_ctx) = create_canvas_element(DROP_SIZE * 2, DROP_SIZE * 2)?;
+        clear_ctx.set_fill_style(&JsValue::from("#000"));
+        clear_ctx.begin_path();
+        clear_ctx.arc(
+            DROP_SIZE as f64,
+            DROP_SIZE as f64,
+            DROP_SIZE as f64,
+            0.0,
+            PI * 2.0,
+        )?;
+        clear_ctx.fill();
+
+        self.clear = Some(clear);
+        Ok(())
+    }
+}
+
+/// 由 Canvas2D 描画的水面纹理，直接作为 WebGL 纹理上传
+pub struct CanvasWaterMap {
+    pub texture: Rc<RefCell<Texture>>,
+    sprites: Rc<RefCell<CanvasSprites>>,
+}
+
+impl CanvasWaterMap {
+    pub fn new(w: u32, h: u32, sprites: Rc<RefCell<CanvasSprites>>) -> Self {
+        let (canvas, ctx) = create_canvas_element(w, h).unwrap();
+        CanvasWaterMap {
+            texture: Rc::new(RefCell::new(Texture { canvas, ctx })),
+            sprites,
+        }
+    }
+
+    pub fn sprites(&self) -> Rc<RefCell<CanvasSprites>> {
+        self.sprites.clone()
+    }
+}
+
+impl WaterMap for CanvasWaterMap {
+    fn clear(&mut self) {
+        let texture = self.texture.borrow();
+        let (w, h) = (texture.canvas.width(), texture.canvas.height());
+        texture.ctx.clear_rect(0.0, 0.0, w as f64, h as f64);
+    }
+
+    fn draw_drop(&mut self, depth: f64, x: f64, y: f64, w: f64, h: f64) {
+        let sprites = self.sprites.borrow();
+        if sprites.drops.is_empty() {
+            return;
+        }
+        let d = (depth * (sprites.drops.len() - 1) as f64).floor();
+
+        let ctx = &self.texture.borrow().ctx;
+        ctx.set_global_alpha(1.0);
+        // 新图像会覆盖在原有图像
+        ctx.set_global_composite_operation("source-over").unwrap();
+        ctx.draw_image_with_html_canvas_element_and_dw_and_dh(
+            &sprites.drops[d as usize],
+            x,
+            y,
+            w,
+            h,
+        )
+        .unwrap();
+    }
+
+    fn erase(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        let sprites = self.sprites.borrow();
+        let clear = match &sprites.clear {
+            Some(clear) => clear,
+            None => return,
+        };
+        let ctx = &self.texture.borrow().ctx;
+        ctx.set_global_composite_operation("destination-out")
+            .unwrap();
+        ctx.draw_image_with_html_canvas_element_and_dw_and_dh(clear, x, y, w, h)
+            .unwrap();
+    }
+
+    fn fade(&mut self, alpha: f64) {
+        let texture = self.texture.borrow();
+        let (w, h) = (texture.canvas.width(), texture.canvas.height());
+        // 绘制原图和新图不重叠部分
+        texture
+            .ctx
+            .set_global_composite_operation("destination-out")
+            .unwrap();
+
+        // 半透明黑色
+        texture
+            .ctx
+            .set_fill_style(&JsValue::from(format!("rgba(0,0,0,{})", alpha)));
+        texture.ctx.fill_rect(0.0, 0.0, w as f64, h as f64);
+    }
+
+    fn draw_streak(&mut self, from: (f64, f64), to: (f64, f64), width: f64, depth: u8) {
+        let ctx = &self.texture.borrow().ctx;
+        ctx.set_global_alpha(1.0);
+        ctx.set_global_composite_operation("source-over").unwrap();
+        ctx.set_line_cap("round");
+        ctx.set_line_width(width);
+        ctx.set_stroke_style_str(&format!("rgba(128,128,{},0.75)", depth));
+        ctx.begin_path();
+        ctx.move_to(from.0, from.1);
+        ctx.line_to(to.0, to.1);
+        ctx.stroke();
+    }
+
+    fn draw_layer(&mut self, layer: &Self) {
+        let texture = self.texture.borrow();
+        let (w, h) = (texture.canvas.width(), texture.canvas.height());
+        texture
+            .ctx
+            .draw_image_with_html_canvas_element_and_dw_and_dh(
+                &layer.texture.borrow().canvas,
+                0.0,
+                0.0,
+                w as f64,
+                h as f64,
+            )
+            .unwrap();
+    }
+
+    fn resize(&mut self, w: u32, h: u32) {
+        let texture = self.texture.borrow();
+        let (old_w, old_h) = (texture.canvas.width(), texture.canvas.height());
+        if (old_w, old_h) == (w, h) {
+            return;
+        }
+        // 改变画布大小会清空内容，先复制一份
+        let copy = if old_w > 0 && old_h > 0 {
+            let (copy, copy_ctx) = create_canvas_element(old_w, old_h).unwrap();
+            copy_ctx
+                .draw_image_with_html_canvas_element(&texture.canvas, 0.0, 0.0)
+                .unwrap();
+            Some(copy)
+        } else {
+            None
+        };
+
+        texture.canvas.set_width(w);
+        texture.canvas.set_height(h);
+        if let Some(copy) = copy {
+            texture
+                .ctx
+                .draw_image_with_html_canvas_element_and_dw_and_dh(
+                    &copy, 0.0, 0.0, w as f64, h as f64,
+                )
+                .unwrap();
+        }
+    }
+}
+
+/// 渲染时使用的水面纹理
+#[derive(Clone)]
+pub enum WaterMapSource {
+    /// Canvas2D 画布，每帧上传
+    Canvas(Rc<RefCell<Texture>>),
+    /// GPU 上的渲染目标，直接采样
+    Gpu(Rc<RefCell<RenderTarget>>),
+}
+
+impl WaterMapSource {
+    /// Canvas2D 画布，GPU 上的纹理返回 None
+    pub fn canvas(&self) -> Option<&Rc<RefCell<Texture>>> {
+        match self {
+            WaterMapSource::Canvas(texture) => Some(texture),
+            WaterMapSource::Gpu(_) => None,
+        }
+    }
+}
+
+/// 浏览器中的水面纹理：WebGL2 下在 GPU 上描画，否则由 Canvas2D 描画
+///
+/// Example:
+/// ```rust
+/// let water_map = match GpuWaterMap::new(w, h, gpu_sprites) {
+///     Some(water_map) => WebWaterMap::Gpu(water_map),
+///     None => WebWaterMap::Canvas(CanvasWaterMap::new(w, h, sprites)),
+/// };
+/// rain_render.set_water_map(water_map.source());
+/// ```
+pub enum WebWaterMap {
+    Canvas(CanvasWaterMap),
+    Gpu(GpuWaterMap),
+}
+
+impl WebWaterMap {
+    pub fn source(&self) -> WaterMapSource {
+        match self {
+            WebWaterMap::Canvas(water_map) => WaterMapSource::Canvas(water_map.texture.clone()),
+            WebWaterMap::Gpu(water_map) => WaterMapSource::Gpu(water_map.target()),
+        }
+    }
+
+    /// 描画 Canvas2D 用的精灵
+    pub fn render_sprites(&self) -> Result<(), JsValue> {
+        match self {
+            WebWaterMap::Canvas(water_map) => water_map.sprites().borrow_mut().render(),
+            WebWaterMap::Gpu(_) => Ok(()),
+        }
+    }
+
+    /// WebGL 上下文恢复后重新创建 GPU 上的纹理
+    pub fn restore(&mut self) {
+        if let WebWaterMap::Gpu(water_map) = self {
+            water_map.restore();
+        }
+    }
+}
+
+// 按实际的纹理转发
+macro_rules! water_map {
+    ($self:expr, $map:ident => $body:expr) => {
+        match $self {
+            WebWaterMap::Canvas($map) => $body,
+            WebWaterMap::Gpu($map) => $body,
+        }
+    };
+}
+
+impl WaterMap for WebWaterMap {
+    fn clear(&mut self) {
+        water_map!(self, map => map.clear())
+    }
+
+    fn draw_drop(&mut self, depth: f64, x: f64, y: f64, w: f64, h: f64) {
+        water_map!(self, map => map.draw_drop(depth, x, y, w, h))
+    }
+
+    fn erase(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        water_map!(self, map => map.erase(x, y, w, h))
+    }
+
+    fn fade(&mut self, alpha: f64) {
+        water_map!(self, map => map.fade(alpha))
+    }
+
+    fn draw_streak(&mut self, from: (f64, f64), to: (f64, f64), width: f64, depth: u8) {
+        water_map!(self, map => map.draw_streak(from, to, width, depth))
+    }
+
+    fn draw_layer(&mut self, layer: &Self) {
+        match (self, layer) {
+            (WebWaterMap::Canvas(map), WebWaterMap::Canvas(layer)) => map.draw_layer(layer),
+            (WebWaterMap::Gpu(map), WebWaterMap::Gpu(layer)) => map.draw_layer(layer),
+            // 水面纹理和雨滴层总是同时在 GPU 或 Canvas2D 上
+            _ => {}
+        }
+    }
+
+    fn resize(&mut self, w: u32, h: u32) {
+        water_map!(self, map => map.resize(w, h))
+    }
+
+    fn flush(&mut self) {
+        water_map!(self, map => map.flush())
+    }
+}
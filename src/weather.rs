@@ -1,8 +1,10 @@
 use crate::images::WeatherImage;
+use crate::spawn::SpawnStrategy;
 use std::cell::RefCell;
 use std::fmt;
 use std::rc::Rc;
 
+#[derive(Clone)]
 pub struct WeatherOptions {
     pub raining: bool,
     pub r: (f64, f64),
@@ -16,6 +18,10 @@ pub struct WeatherOptions {
     pub flash: Option<Rc<RefCell<WeatherImage>>>,
     pub flash_chance: f64,
     pub collision_radius_increase: f64,
+    /// 阵风强度（0 为均匀的雨）
+    pub gust_strength: f64,
+    /// 雨点生成策略（None 时沿用当前策略）
+    pub spawn: Option<Rc<RefCell<dyn SpawnStrategy>>>,
 }
 
 impl Default for WeatherOptions {
@@ -33,6 +39,8 @@ impl Default for WeatherOptions {
             flash: None,
             flash_chance: 0.0,
             collision_radius_increase: 0.0002,
+            gust_strength: 0.5,
+            spawn: None,
         }
     }
 }
@@ -41,6 +49,99 @@ impl WeatherOptions {
     pub fn new() -> Self {
         WeatherOptions::default()
     }
+
+    /// 天气预设（rain / fallout / storm / sun / drizzle，未知名称按 rain 处理），不含图片
+    ///
+    /// Example:
+    /// ```rust
+    /// let opts = WeatherOptions::preset("storm");
+    /// ```
+    pub fn preset(name: &str) -> WeatherOptions {
+        let mut opts = WeatherOptions::default();
+        match name {
+            "fallout" => {
+                opts.r = (30.0, 60.0);
+                opts.rain_chance = 0.35;
+                opts.droplets_rate = 20.0;
+                opts.trail_rate = 4.0;
+                opts.collision_radius_increase = 0.0;
+                opts.gust_strength = 0.3;
+            }
+            "storm" => {
+                opts.r = (20.0, 55.0);
+                opts.rain_chance = 0.4;
+                opts.droplets_rate = 80.0;
+                opts.droplets_size = (3.0, 5.5);
+                opts.trail_rate = 2.5;
+                opts.trail_scale_range = [0.25, 0.4];
+                opts.flash_chance = 0.1;
+                opts.gust_strength = 0.9;
+            }
+            "sun" => {
+                opts.rain_chance = 0.0;
+                opts.rain_limit = 0.0;
+                opts.droplets_rate = 0.0;
+                opts.raining = false;
+                opts.gust_strength = 0.0;
+            }
+            "drizzle" => {
+                opts.r = (10.0, 40.0);
+                opts.rain_chance = 0.15;
+                opts.rain_limit = 2.0;
+                opts.droplets_rate = 10.0;
+                opts.droplets_size = (3.5, 6.0);
+                opts.gust_strength = 0.3;
+            }
+            _ => {
+                opts.rain_chance = 0.35;
+                opts.droplets_rate = 50.0;
+                opts.raining = true;
+            }
+        }
+        opts
+    }
+
+    /// 在两组天气参数之间插值（t: 0.0 ~ 1.0）
+    ///
+    /// 过渡期间只要任意一方在下雨就保持下雨，雨量随数值参数渐变，
+    /// 到达终点后才切换为目标的 `raining`。
+    ///
+    /// Example:
+    /// ```rust
+    /// let opts = rain.options().lerp(sun.options(), 0.5);
+    /// ```
+    pub fn lerp(&self, other: &WeatherOptions, t: f64) -> WeatherOptions {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: f64, b: f64| a + (b - a) * t;
+        let mix2 = |a: (f64, f64), b: (f64, f64)| (mix(a.0, b.0), mix(a.1, b.1));
+
+        WeatherOptions {
+            raining: if t >= 1.0 {
+                other.raining
+            } else {
+                self.raining || other.raining
+            },
+            r: mix2(self.r, other.r),
+            rain_limit: mix(self.rain_limit, other.rain_limit),
+            rain_chance: mix(self.rain_chance, other.rain_chance),
+            droplets_rate: mix(self.droplets_rate, other.droplets_rate),
+            droplets_size: mix2(self.droplets_size, other.droplets_size),
+            trail_rate: mix(self.trail_rate, other.trail_rate),
+            trail_scale_range: [
+                mix(self.trail_scale_range[0], other.trail_scale_range[0]),
+                mix(self.trail_scale_range[1], other.trail_scale_range[1]),
+            ],
+            base: other.base.clone(),
+            flash: other.flash.clone(),
+            flash_chance: mix(self.flash_chance, other.flash_chance),
+            collision_radius_increase: mix(
+                self.collision_radius_increase,
+                other.collision_radius_increase,
+            ),
+            gust_strength: mix(self.gust_strength, other.gust_strength),
+            spawn: other.spawn.clone().or_else(|| self.spawn.clone()),
+        }
+    }
 }
 
 pub enum Weather {
@@ -73,60 +174,22 @@ impl Weather {
         }
     }
 
-    pub fn new_with_img(rc_img: Rc<RefCell<WeatherImage>>) -> Self {
-        let mut opts = WeatherOptions::default();
-        let img = &*rc_img.borrow();
-        match img {
-            WeatherImage::Rain(_) => {
-                opts.rain_chance = 0.35;
-                opts.droplets_rate = 50.0;
-                opts.raining = true;
-                opts.base = Some(rc_img.clone());
-
-                Weather::Rain(opts)
-            }
-            WeatherImage::Fallout(_) => {
-                opts.r = (30.0, 60.0);
-                opts.rain_chance = 0.35;
-                opts.droplets_rate = 20.0;
-                opts.trail_rate = 4.0;
-                opts.base = Some(rc_img.clone());
-                opts.collision_radius_increase = 0.0;
-
-                Weather::Fallout(opts)
-            }
-            WeatherImage::Storm(_) => {
-                opts.r = (20.0, 55.0);
-                opts.rain_chance = 0.4;
-                opts.droplets_rate = 80.0;
-                opts.droplets_size = (3.0, 5.5);
-                opts.trail_rate = 2.5;
-                opts.trail_scale_range = [0.25, 0.4];
-                opts.base = Some(rc_img.clone());
-                opts.flash_chance = 0.1;
-
-                Weather::Storm(opts)
-            }
-            WeatherImage::Sun(_) => {
-                opts.rain_chance = 0.0;
-                opts.rain_limit = 0.0;
-                opts.droplets_rate = 0.0;
-                opts.raining = false;
-                opts.base = Some(rc_img.clone());
+    pub fn options_mut(&mut self) -> &mut WeatherOptions {
+        match self {
+            Weather::Rain(opts)
+            | Weather::Fallout(opts)
+            | Weather::Storm(opts)
+            | Weather::Sun(opts)
+            | Weather::Drizzle(opts) => opts,
+        }
+    }
 
-                Weather::Sun(opts)
-            }
-            WeatherImage::Drizzle(_) => {
-                opts.r = (10.0, 40.0);
-                opts.rain_chance = 0.15;
-                opts.rain_limit = 2.0;
-                opts.droplets_rate = 10.0;
-                opts.droplets_size = (3.5, 6.0);
-                opts.base = Some(rc_img.clone());
+    pub fn new_with_img(rc_img: Rc<RefCell<WeatherImage>>) -> Self {
+        let name = rc_img.borrow().to_string();
+        let mut opts = WeatherOptions::preset(&name);
+        opts.base = Some(rc_img.clone());
 
-                Weather::Drizzle(opts)
-            }
-        }
+        Weather::new(&name, opts)
     }
 }
 
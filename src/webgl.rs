@@ -1,20 +1,28 @@
-use crate::shader::{FRAGMENT_SHADER, VERTEX_SHADER};
-use crate::{compile_shader, link_program};
+use crate::shader::{glsl3, FRAGMENT_SHADER, VERTEX_SHADER};
 use js_sys::Float32Array;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::{console, HtmlCanvasElement, WebGlProgram, WebGlRenderingContext};
+use web_sys::{
+    console, HtmlCanvasElement, WebGl2RenderingContext, WebGlBuffer, WebGlProgram,
+    WebGlRenderingContext, WebGlShader, WebGlTexture, WebGlVertexArrayObject,
+};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct WebGlOptions {
     pub alpha: bool,
+    /// 是否优先使用 WebGL2（不支持时回退到 WebGL1）
+    #[serde(skip)]
+    pub webgl2: bool,
 }
 
 impl Default for WebGlOptions {
     fn default() -> Self {
-        WebGlOptions { alpha: false }
+        WebGlOptions {
+            alpha: false,
+            webgl2: true,
+        }
     }
 }
 
@@ -30,214 +38,525 @@ pub enum UniformType {
     F2(f32, f32),
 }
 
+/// WebGL 上下文：WebGL2 或 WebGL1
+///
+/// 两者的常量相同，统一使用 `WebGlRenderingContext` 中的常量。
+pub enum Context {
+    WebGl2(WebGl2RenderingContext),
+    WebGl1(WebGlRenderingContext),
+}
+
+// 两种上下文中同名的方法直接转发
+macro_rules! gl {
+    ($ctx:expr, $gl:ident => $body:expr) => {
+        match $ctx {
+            Context::WebGl2($gl) => $body,
+            Context::WebGl1($gl) => $body,
+        }
+    };
+}
+
+impl Context {
+    /// 获取上下文，webgl2 为 true 时优先尝试 WebGL2
+    ///
+    /// Example:
+    /// ```rust
+    /// let gl = Context::new(&canvas, &JsValue::NULL, true).unwrap();
+    /// ```
+    pub fn new(canvas: &HtmlCanvasElement, attrs: &JsValue, webgl2: bool) -> Option<Context> {
+        if webgl2 {
+            let gl = canvas
+                .get_context_with_context_options("webgl2", attrs)
+                .ok()
+                .flatten()
+                .and_then(|gl| gl.dyn_into::<WebGl2RenderingContext>().ok());
+            if let Some(gl) = gl {
+                return Some(Context::WebGl2(gl));
+            }
+        }
+        canvas
+            .get_context_with_context_options("webgl", attrs)
+            .ok()
+            .flatten()
+            .and_then(|gl| gl.dyn_into::<WebGlRenderingContext>().ok())
+            .map(Context::WebGl1)
+    }
+
+    /// WebGL 版本（1 或 2）
+    pub fn version(&self) -> u32 {
+        match self {
+            Context::WebGl2(_) => 2,
+            Context::WebGl1(_) => 1,
+        }
+    }
+
+    /// 启用浮点渲染目标所需的扩展，返回是否支持
+    ///
+    /// WebGL2 需要 `EXT_color_buffer_float`，WebGL1 需要半精度浮点纹理和对应的颜色缓冲扩展。
+    fn enable_float_render_targets(&self) -> bool {
+        let extension =
+            |name: &str| gl!(self, gl => gl.get_extension(name).ok().flatten().is_some());
+        match self {
+            Context::WebGl2(_) => extension("EXT_color_buffer_float"),
+            Context::WebGl1(_) => {
+                extension("OES_texture_half_float") && extension("EXT_color_buffer_half_float")
+            }
+        }
+    }
+
+    /// 上传纹理时预乘透明度
+    ///
+    /// 画布本身保存预乘的像素，上传时不需要转换；GPU 上描画的水面纹理也是预乘的，
+    /// 着色器对两者同样处理。
+    fn premultiply_uploads(&self) {
+        gl!(self, gl => gl.pixel_storei(WebGlRenderingContext::UNPACK_PREMULTIPLY_ALPHA_WEBGL, 1));
+    }
+
+    /// 创建并绑定顶点数组对象，WebGL2 的顶点属性状态保存在其中；WebGL1 返回 None
+    fn create_vertex_array(&self) -> Option<WebGlVertexArrayObject> {
+        match self {
+            Context::WebGl2(gl) => {
+                let vao = gl.create_vertex_array();
+                gl.bind_vertex_array(vao.as_ref());
+                vao
+            }
+            Context::WebGl1(_) => None,
+        }
+    }
+
+    /// 编译着色器，WebGL2 下先转换为 GLSL ES 3.00
+    pub fn compile_shader(&self, shader_type: u32, source: &str) -> Result<WebGlShader, String> {
+        let source = match self {
+            Context::WebGl2(_) => glsl3(source, shader_type),
+            Context::WebGl1(_) => source.to_owned(),
+        };
+        gl!(self, gl => {
+            let shader = gl
+                .create_shader(shader_type)
+                .ok_or_else(|| String::from("Unable to create shader object"))?;
+            gl.shader_source(&shader, &source);
+            gl.compile_shader(&shader);
+
+            if gl
+                .get_shader_parameter(&shader, WebGlRenderingContext::COMPILE_STATUS)
+                .as_bool()
+                .unwrap_or(false)
+            {
+                Ok(shader)
+            } else {
+                let err = gl
+                    .get_shader_info_log(&shader)
+                    .unwrap_or_else(|| String::from("Unknown error creating shader"));
+                gl.delete_shader(Some(&shader));
+                Err(err)
+            }
+        })
+    }
+
+    /// 链接程序
+    pub fn link_program(
+        &self,
+        vert: &WebGlShader,
+        frag: &WebGlShader,
+    ) -> Result<WebGlProgram, String> {
+        gl!(self, gl => {
+            let program = gl
+                .create_program()
+                .ok_or_else(|| String::from("Unable to create shader object"))?;
+            gl.attach_shader(&program, vert);
+            gl.attach_shader(&program, frag);
+            gl.link_program(&program);
+
+            if gl
+                .get_program_parameter(&program, WebGlRenderingContext::LINK_STATUS)
+                .as_bool()
+                .unwrap_or(false)
+            {
+                Ok(program)
+            } else {
+                let err = gl
+                    .get_program_info_log(&program)
+                    .unwrap_or_else(|| String::from("Unknown error creating program object"));
+                gl.delete_program(Some(&program));
+                Err(err)
+            }
+        })
+    }
+}
+
+/// 纹理单元上的纹理，由 `WebGl` 持有，`destroy` 或析构时删除
+struct TextureSlot {
+    // 采样器 uniform 的名称（不含 u_ 前缀）
+    name: &'static str,
+    unit: u32,
+    texture: WebGlTexture,
+    // 已分配的大小，大小不变时用 texSubImage2D 更新
+    size: Option<(u32, u32)>,
+}
+
+// 铺满画面的两个三角形
+const QUAD: [f32; 12] = [
+    -1.0, -1.0, 1.0, -1.0, -1.0, 1.0, -1.0, 1.0, 1.0, -1.0, 1.0, 1.0,
+];
+
 pub struct WebGl {
     // 背景宽度
     width: f64,
     // 背景高度
     height: f64,
     canvas: Rc<RefCell<HtmlCanvasElement>>,
-    gl: WebGlRenderingContext,
+    gl: Context,
     program: WebGlProgram,
+    // 顶点数组对象（仅 WebGL2）
+    vao: Option<WebGlVertexArrayObject>,
+    // 顶点缓冲区，创建时上传一次
+    quad: Option<WebGlBuffer>,
+    textures: RefCell<Vec<TextureSlot>>,
+    // 是否支持浮点渲染目标
+    float_render_targets: bool,
 }
 
 impl WebGl {
-    pub fn new(canvas: Rc<RefCell<HtmlCanvasElement>>, opts: Option<WebGlOptions>) -> Self {
+    /// 创建 WebGL 上下文和程序，浏览器不支持 WebGL 或着色器编译失败时返回错误
+    ///
+    /// 着色器编译失败时上下文已经创建，画布不能再退回 Canvas2D。
+    pub fn new(
+        canvas: Rc<RefCell<HtmlCanvasElement>>,
+        opts: Option<WebGlOptions>,
+    ) -> Result<Self, String> {
         let opts = match opts {
             Some(opts) => opts,
             None => WebGlOptions::new(),
         };
-        let opts = JsValue::from_serde(&opts).unwrap();
-        let gl = canvas
-            .borrow()
-            .get_context_with_context_options("webgl", &opts)
-            .unwrap()
-            .unwrap()
-            .dyn_into::<WebGlRenderingContext>()
-            .unwrap();
+        let attrs = JsValue::from_serde(&opts).unwrap();
+        let gl = Context::new(&canvas.borrow(), &attrs, opts.webgl2)
+            .ok_or_else(|| String::from("WebGL is unavailable"))?;
+        let float_render_targets = gl.enable_float_render_targets();
+        gl.premultiply_uploads();
 
         let width = canvas.borrow().width() as f64;
         let height = canvas.borrow().height() as f64;
 
-        let program = WebGl::create_program(&gl);
+        let vao = gl.create_vertex_array();
+        let (program, quad) = WebGl::create_program(&gl)?;
 
-        gl.use_program(program.as_ref());
+        gl!(&gl, gl => gl.use_program(Some(&program)));
 
-        WebGl {
-            program: program.unwrap(),
+        Ok(WebGl {
+            program,
+            vao,
+            quad,
+            textures: RefCell::new(Vec::new()),
+            float_render_targets,
             gl,
             canvas,
             width,
             height,
-        }
+        })
     }
 
-    fn create_program(context: &WebGlRenderingContext) -> Option<WebGlProgram> {
+    /// 创建程序和顶点缓冲区
+    fn create_program(context: &Context) -> Result<(WebGlProgram, Option<WebGlBuffer>), String> {
         let vert_shader =
-            compile_shader(context, WebGlRenderingContext::VERTEX_SHADER, VERTEX_SHADER).unwrap();
-
-        let frag_shader = compile_shader(
-            context,
-            WebGlRenderingContext::FRAGMENT_SHADER,
-            FRAGMENT_SHADER,
-        )
-        .unwrap();
-
-        let program = link_program(&context, &vert_shader, &frag_shader).unwrap();
-
-        let linked = context.get_program_parameter(&program, WebGlRenderingContext::LINK_STATUS);
-        if !linked.as_bool().unwrap() {
-            let err = context.get_program_info_log(&program).unwrap();
-            console::error_1(&JsValue::from(err));
-            context.delete_program(Some(&program));
-            return None;
-        }
+            context.compile_shader(WebGlRenderingContext::VERTEX_SHADER, VERTEX_SHADER)?;
 
-        let a_position = context.get_attrib_location(&program, "a_position");
-        let a_tex_coord = context.get_attrib_location(&program, "a_texCoord");
-
-        let buffer = context.create_buffer();
-        context.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, buffer.as_ref());
-        let vertices: [f32; 12] = [
-            -1.0, -1.0, 1.0, -1.0, -1.0, 1.0, -1.0, 1.0, 1.0, -1.0, 1.0, 1.0,
-        ];
-        unsafe {
-            let vert_array = Float32Array::view(&vertices);
-            context.buffer_data_with_array_buffer_view(
-                WebGlRenderingContext::ARRAY_BUFFER,
-                &vert_array,
-                WebGlRenderingContext::STATIC_DRAW,
+        let frag_shader =
+            context.compile_shader(WebGlRenderingContext::FRAGMENT_SHADER, FRAGMENT_SHADER)?;
+
+        let program = context.link_program(&vert_shader, &frag_shader)?;
+
+        gl!(context, context => {
+            // 着色器链接后即可删除
+            context.delete_shader(Some(&vert_shader));
+            context.delete_shader(Some(&frag_shader));
+
+            let linked = context.get_program_parameter(&program, WebGlRenderingContext::LINK_STATUS);
+            if !linked.as_bool().unwrap() {
+                let err = context.get_program_info_log(&program).unwrap();
+                console::error_1(&JsValue::from(&err));
+                context.delete_program(Some(&program));
+                return Err(err);
+            }
+
+            let a_position = context.get_attrib_location(&program, "a_position");
+
+            // 几何形状不会变化，只上传一次
+            let quad = context.create_buffer();
+            context.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, quad.as_ref());
+            unsafe {
+                let vert_array = Float32Array::view(&QUAD);
+                context.buffer_data_with_array_buffer_view(
+                    WebGlRenderingContext::ARRAY_BUFFER,
+                    &vert_array,
+                    WebGlRenderingContext::STATIC_DRAW,
+                );
+            }
+
+            context.enable_vertex_attrib_array(a_position as u32);
+            context.vertex_attrib_pointer_with_i32(
+                a_position as u32,
+                2,
+                WebGlRenderingContext::FLOAT,
+                false,
+                0,
+                0,
             );
+
+            Ok((program, quad))
+        })
+    }
+
+    /// WebGL 版本（1 或 2）
+    pub fn version(&self) -> u32 {
+        self.gl.version()
+    }
+
+    /// WebGL2 上下文，用于在同一上下文中描画水面纹理；WebGL1 返回 None
+    pub fn webgl2(&self) -> Option<WebGl2RenderingContext> {
+        match &self.gl {
+            Context::WebGl2(gl) => Some(gl.clone()),
+            Context::WebGl1(_) => None,
         }
+    }
 
-        context.enable_vertex_attrib_array(a_tex_coord as u32);
-        context.vertex_attrib_pointer_with_i32(
-            a_tex_coord as u32,
-            2,
-            WebGlRenderingContext::FLOAT,
-            false,
-            0,
-            0,
-        );
+    /// 画布大小变化后更新视口
+    pub fn resize(&mut self, w: u32, h: u32) {
+        self.width = w as f64;
+        self.height = h as f64;
+        gl!(&self.gl, gl => gl.viewport(0, 0, w as i32, h as i32));
+    }
 
-        let buffer = context.create_buffer();
-        context.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, buffer.as_ref());
+    pub fn is_context_lost(&self) -> bool {
+        gl!(&self.gl, gl => gl.is_context_lost())
+    }
 
-        context.enable_vertex_attrib_array(a_position as u32);
-        context.vertex_attrib_pointer_with_i32(
-            a_position as u32,
-            2,
-            WebGlRenderingContext::FLOAT,
-            false,
-            0,
-            0,
-        );
+    /// 上下文恢复后重新创建程序和缓冲区
+    ///
+    /// 丢失前的 WebGL 对象都已失效，纹理需要重新调用 `create_texture` 创建。
+    pub fn restore(&mut self) -> Result<(), String> {
+        self.textures.borrow_mut().clear();
+        self.float_render_targets = self.gl.enable_float_render_targets();
+        self.gl.premultiply_uploads();
+        self.vao = self.gl.create_vertex_array();
+
+        let (program, quad) = WebGl::create_program(&self.gl)?;
+        gl!(&self.gl, gl => gl.use_program(Some(&program)));
+        self.program = program;
+        self.quad = quad;
+        Ok(())
+    }
 
-        Some(program)
+    /// 是否支持浮点渲染目标（可用作高精度的水面纹理）
+    pub fn float_render_targets(&self) -> bool {
+        self.float_render_targets
     }
 
     pub fn use_program(&self) {
-        self.gl.use_program(Some(&self.program));
+        gl!(&self.gl, gl => gl.use_program(Some(&self.program)));
+        if let Context::WebGl2(gl) = &self.gl {
+            gl.bind_vertex_array(self.vao.as_ref());
+        }
     }
 
     pub fn create_uniform(&self, ut: UniformType, name: &str) {
-        let location = self
-            .gl
-            .get_uniform_location(&self.program, &format!("u_{}", name));
-        match ut {
-            UniformType::F1(x) => {
-                self.gl.uniform1f(location.as_ref(), x);
-            }
-            UniformType::I1(x) => {
-                self.gl.uniform1i(location.as_ref(), x);
-            }
-            UniformType::F2(x, y) => {
-                self.gl.uniform2f(location.as_ref(), x, y);
-            }
-        };
+        gl!(&self.gl, gl => {
+            let location = gl.get_uniform_location(&self.program, &format!("u_{}", name));
+            match ut {
+                UniformType::F1(x) => {
+                    gl.uniform1f(location.as_ref(), x);
+                }
+                UniformType::I1(x) => {
+                    gl.uniform1i(location.as_ref(), x);
+                }
+                UniformType::F2(x, y) => {
+                    gl.uniform2f(location.as_ref(), x, y);
+                }
+            };
+        })
     }
 
-    pub fn create_texture(&self, source: Option<&HtmlCanvasElement>, idx: u32) {
-        let texture = self.gl.create_texture();
-        let idx = match idx {
-            1 => WebGlRenderingContext::TEXTURE1,
-            2 => WebGlRenderingContext::TEXTURE2,
-            3 => WebGlRenderingContext::TEXTURE3,
-            _ => WebGlRenderingContext::TEXTURE0,
+    /// 在纹理单元 unit 上创建名为 name 的纹理，并把采样器 `u_<name>` 指向该单元
+    ///
+    /// 同名的纹理已存在时先删除。
+    ///
+    /// Example:
+    /// ```rust
+    /// gl.create_texture("textureFg", 2, Some(&fg.canvas));
+    /// gl.update_texture("textureFg", &fg.canvas);
+    /// ```
+    pub fn create_texture(
+        &self,
+        name: &'static str,
+        unit: u32,
+        source: Option<&HtmlCanvasElement>,
+    ) {
+        let texture = match gl!(&self.gl, gl => gl.create_texture()) {
+            Some(texture) => texture,
+            None => return,
         };
-        self.active_texture(idx);
-        self.gl
-            .bind_texture(WebGlRenderingContext::TEXTURE_2D, texture.as_ref());
-
-        self.gl.tex_parameteri(
-            WebGlRenderingContext::TEXTURE_2D,
-            WebGlRenderingContext::TEXTURE_WRAP_S,
-            WebGlRenderingContext::CLAMP_TO_EDGE as i32,
-        );
-
-        self.gl.tex_parameteri(
-            WebGlRenderingContext::TEXTURE_2D,
-            WebGlRenderingContext::TEXTURE_WRAP_T,
-            WebGlRenderingContext::CLAMP_TO_EDGE as i32,
-        );
-
-        self.gl.tex_parameteri(
-            WebGlRenderingContext::TEXTURE_2D,
-            WebGlRenderingContext::TEXTURE_MIN_FILTER,
-            WebGlRenderingContext::LINEAR as i32,
-        );
-
-        self.gl.tex_parameteri(
-            WebGlRenderingContext::TEXTURE_2D,
-            WebGlRenderingContext::TEXTURE_MAG_FILTER,
-            WebGlRenderingContext::LINEAR as i32,
-        );
-
-        match source {
-            Some(source) => self.update_texture(source),
-            None => {
-                println!("create_texture source none");
+        self.active_texture(unit);
+        self.bind_texture(&texture);
+
+        {
+            let mut textures = self.textures.borrow_mut();
+            if let Some(i) = textures.iter().position(|slot| slot.name == name) {
+                let old = textures.swap_remove(i);
+                gl!(&self.gl, gl => gl.delete_texture(Some(&old.texture)));
             }
-        };
-    }
+            textures.push(TextureSlot {
+                name,
+                unit,
+                texture,
+                size: None,
+            });
+        }
+        self.create_uniform(UniformType::I1(unit as i32), name);
 
-    pub fn active_texture(&self, idx: u32) {
-        let idx = match idx {
-            1 => WebGlRenderingContext::TEXTURE1,
-            2 => WebGlRenderingContext::TEXTURE2,
-            3 => WebGlRenderingContext::TEXTURE3,
-            _ => WebGlRenderingContext::TEXTURE0,
-        };
-        self.gl.active_texture(idx);
+        if let Some(source) = source {
+            self.update_texture(name, source);
+        }
     }
 
-    pub fn update_texture(&self, source: &HtmlCanvasElement) {
-        self.gl
-            .tex_image_2d_with_u32_and_u32_and_canvas(
+    // 绑定纹理并设置采样参数
+    fn bind_texture(&self, texture: &WebGlTexture) {
+        gl!(&self.gl, gl => {
+            gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(texture));
+
+            gl.tex_parameteri(
                 WebGlRenderingContext::TEXTURE_2D,
-                0,
-                WebGlRenderingContext::RGBA as i32,
-                WebGlRenderingContext::RGBA,
-                WebGlRenderingContext::UNSIGNED_BYTE,
-                source,
-            )
-            .unwrap();
-    }
-
-    fn set_rectangle(&self, x: f32, y: f32, w: f32, h: f32) {
-        let (x1, x2, y1, y2) = (x, x + w, y, y + h);
-        let vertices: [f32; 12] = [x1, y1, x2, y1, x1, y2, x1, y2, x2, y1, x2, y2];
-        unsafe {
-            let vert_array = Float32Array::view(&vertices);
-            self.gl.buffer_data_with_array_buffer_view(
-                WebGlRenderingContext::ARRAY_BUFFER,
-                &vert_array,
-                WebGlRenderingContext::STATIC_DRAW,
+                WebGlRenderingContext::TEXTURE_WRAP_S,
+                WebGlRenderingContext::CLAMP_TO_EDGE as i32,
+            );
+
+            gl.tex_parameteri(
+                WebGlRenderingContext::TEXTURE_2D,
+                WebGlRenderingContext::TEXTURE_WRAP_T,
+                WebGlRenderingContext::CLAMP_TO_EDGE as i32,
+            );
+
+            gl.tex_parameteri(
+                WebGlRenderingContext::TEXTURE_2D,
+                WebGlRenderingContext::TEXTURE_MIN_FILTER,
+                WebGlRenderingContext::LINEAR as i32,
+            );
+
+            gl.tex_parameteri(
+                WebGlRenderingContext::TEXTURE_2D,
+                WebGlRenderingContext::TEXTURE_MAG_FILTER,
+                WebGlRenderingContext::LINEAR as i32,
             );
+        })
+    }
+
+    fn active_texture(&self, idx: u32) {
+        gl!(&self.gl, gl => gl.active_texture(WebGlRenderingContext::TEXTURE0 + idx));
+    }
+
+    /// 把画布上传到名为 name 的纹理
+    ///
+    /// 大小不变时用 texSubImage2D 只更新像素；大小变化时重新分配
+    /// （WebGL2 的 texStorage2D 大小不可变，需要重新创建纹理）。
+    pub fn update_texture(&self, name: &str, source: &HtmlCanvasElement) {
+        let size = (source.width(), source.height());
+        if size.0 == 0 || size.1 == 0 {
+            return;
+        }
+        let mut textures = self.textures.borrow_mut();
+        let slot = match textures.iter_mut().find(|slot| slot.name == name) {
+            Some(slot) => slot,
+            None => return,
+        };
+        self.active_texture(slot.unit);
+        gl!(&self.gl, gl => gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&slot.texture)));
+
+        let reallocate = slot.size != Some(size);
+        match &self.gl {
+            Context::WebGl2(gl) => {
+                if reallocate {
+                    if slot.size.is_some() {
+                        gl.delete_texture(Some(&slot.texture));
+                        slot.texture = gl.create_texture().unwrap();
+                        self.bind_texture(&slot.texture);
+                    }
+                    gl.tex_storage_2d(
+                        WebGlRenderingContext::TEXTURE_2D,
+                        1,
+                        WebGl2RenderingContext::RGBA8,
+                        size.0 as i32,
+                        size.1 as i32,
+                    );
+                }
+                gl.tex_sub_image_2d_with_u32_and_u32_and_html_canvas_element(
+                    WebGlRenderingContext::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    WebGlRenderingContext::RGBA,
+                    WebGlRenderingContext::UNSIGNED_BYTE,
+                    source,
+                )
+                .unwrap();
+            }
+            Context::WebGl1(gl) if reallocate => gl
+                .tex_image_2d_with_u32_and_u32_and_canvas(
+                    WebGlRenderingContext::TEXTURE_2D,
+                    0,
+                    WebGlRenderingContext::RGBA as i32,
+                    WebGlRenderingContext::RGBA,
+                    WebGlRenderingContext::UNSIGNED_BYTE,
+                    source,
+                )
+                .unwrap(),
+            Context::WebGl1(gl) => gl
+                .tex_sub_image_2d_with_u32_and_u32_and_canvas(
+                    WebGlRenderingContext::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    WebGlRenderingContext::RGBA,
+                    WebGlRenderingContext::UNSIGNED_BYTE,
+                    source,
+                )
+                .unwrap(),
         }
+        slot.size = Some(size);
+    }
+
+    /// 把不属于 `WebGl` 的纹理（如 GPU 上的水面纹理）绑定到名为 name 的纹理所在的单元
+    pub fn attach_texture(&self, name: &str, texture: &WebGlTexture) {
+        let unit = match self.textures.borrow().iter().find(|slot| slot.name == name) {
+            Some(slot) => slot.unit,
+            None => return,
+        };
+        self.active_texture(unit);
+        gl!(&self.gl, gl => gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(texture)));
     }
 
     pub fn draw(&self) {
-        self.set_rectangle(-1.0, -1.0, 2.0, 2.0);
-        self.gl.draw_arrays(WebGlRenderingContext::TRIANGLES, 0, 6);
+        gl!(&self.gl, gl => gl.draw_arrays(WebGlRenderingContext::TRIANGLES, 0, 6));
+    }
+
+    /// 释放程序、缓冲区和纹理，可以重复调用
+    pub fn destroy(&mut self) {
+        gl!(&self.gl, gl => {
+            for slot in self.textures.borrow_mut().drain(..) {
+                gl.delete_texture(Some(&slot.texture));
+            }
+            if let Some(quad) = self.quad.take() {
+                gl.delete_buffer(Some(&quad));
+            }
+            gl.use_program(None);
+            gl.delete_program(Some(&self.program));
+        });
+        if let (Context::WebGl2(gl), Some(vao)) = (&self.gl, self.vao.take()) {
+            gl.bind_vertex_array(None);
+            gl.delete_vertex_array(Some(&vao));
+        }
+    }
+}
+
+impl Drop for WebGl {
+    fn drop(&mut self) {
+        self.destroy();
     }
 }
@@ -0,0 +1,44 @@
+use rain_effect::blur::{gaussian_blur, gaussian_kernel};
+use rain_effect::rgba::RgbaImage;
+
+#[test]
+fn kernel_is_normalized_and_symmetric() {
+    let kernel = gaussian_kernel(4.5);
+
+    assert_eq!(kernel.len(), 11);
+    assert!((kernel.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+    for i in 0..kernel.len() / 2 {
+        assert_eq!(kernel[i], kernel[kernel.len() - 1 - i]);
+    }
+    assert_eq!(gaussian_kernel(0.0), vec![1.0]);
+}
+
+#[test]
+fn blur_spreads_a_point_and_keeps_flat_areas() {
+    let mut image = RgbaImage::filled(9, 9, [0, 0, 0, 255]);
+    image.set_pixel(4, 4, [255, 255, 255, 255]);
+    gaussian_blur(&mut image, 3.0);
+
+    let center = image.pixel(4, 4)[0];
+    let near = image.pixel(5, 4)[0];
+    assert!(center < 255 && near > 0 && near < center);
+    assert_eq!(image.pixel(5, 4), image.pixel(4, 5));
+    assert_eq!(image.pixel(0, 0), [0, 0, 0, 255]);
+
+    let mut flat = RgbaImage::filled(8, 8, [10, 200, 30, 255]);
+    gaussian_blur(&mut flat, 5.0);
+    assert_eq!(flat, RgbaImage::filled(8, 8, [10, 200, 30, 255]));
+}
+
+#[test]
+fn transparent_pixels_do_not_darken_colors() {
+    let mut image = RgbaImage::new(6, 1);
+    for x in 0..3 {
+        image.set_pixel(x, 0, [255, 0, 0, 255]);
+    }
+    gaussian_blur(&mut image, 2.0);
+
+    let [r, g, b, a] = image.pixel(3, 0);
+    assert_eq!((r, g, b), (255, 0, 0));
+    assert!(a > 0 && a < 255);
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "export")]
+
+use rain_effect::export::{seamless_loop, write_apng, write_gif, AnimationOptions};
+use rain_effect::rgba::RgbaImage;
+
+fn frames() -> Vec<RgbaImage> {
+    (0..4)
+        .map(|i| RgbaImage::filled(8, 6, [i * 60, 100, 200, 255]))
+        .collect()
+}
+
+#[test]
+fn seamless_loop_fades_end_into_start() {
+    let frames = seamless_loop(frames(), 2);
+
+    assert_eq!(frames.len(), 2);
+    // 第一帧是多渲染的第一帧
+    assert_eq!(frames[0].pixel(0, 0), [120, 100, 200, 255]);
+    // 第二帧是多渲染的第二帧和原第二帧各一半
+    assert_eq!(frames[1].pixel(0, 0), [120, 100, 200, 255]);
+}
+
+#[test]
+fn gif_round_trip() {
+    let mut buf = Vec::new();
+    write_gif(&mut buf, &frames(), &AnimationOptions::new()).unwrap();
+
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(buf.as_slice()).unwrap();
+    let (mut count, mut delay) = (0, 0);
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        assert_eq!((frame.width, frame.height), (8, 6));
+        assert_eq!(&frame.buffer[..4], &[count * 60, 100, 200, 255]);
+        count += 1;
+        delay += frame.delay;
+    }
+    assert_eq!(count, 4);
+    // 4 帧 30FPS
+    assert_eq!(delay, 13);
+}
+
+#[test]
+fn apng_round_trip() {
+    let mut buf = Vec::new();
+    write_apng(&mut buf, &frames(), &AnimationOptions::new()).unwrap();
+
+    let reader = png::Decoder::new(buf.as_slice()).read_info().unwrap();
+    let control = reader.info().animation_control().unwrap();
+    assert_eq!(control.num_frames, 4);
+    assert_eq!(control.num_plays, 0);
+}
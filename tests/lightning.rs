@@ -0,0 +1,46 @@
+use rain_effect::lightning::{Flash, Lightning};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn flash_rises_flickers_and_fades_out() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let flash = Flash::new(1000.0, &mut rng);
+
+    assert_eq!(flash.value(1000.0), 0.0);
+    assert_eq!(flash.value(1025.0), 1.0);
+    assert!(flash.duration() >= 25.0 * 3.0 + 350.0);
+    assert!(flash.duration() <= 25.0 * 8.0 + 350.0);
+    let end = 1000.0 + flash.duration();
+    assert!((flash.value(end - 250.0) - 1.0).abs() < 1e-9);
+    assert!(flash.value(end - 125.0) < 0.1);
+    assert_eq!(flash.value(end + 1.0), 0.0);
+
+    let values: Vec<f64> = (0..=flash.duration() as usize)
+        .map(|t| flash.value(1000.0 + t as f64))
+        .collect();
+    assert!(values.iter().all(|v| (0.0..=1.0).contains(v)));
+}
+
+#[test]
+fn lightning_follows_the_flash_chance() {
+    let mut never = Lightning::new(Some(1));
+    let mut always = Lightning::new(Some(1));
+    for i in 0..20 {
+        never.update(i as f64 * 100.0, 0.0);
+        always.update(i as f64 * 100.0, 1.0);
+    }
+
+    assert!(!never.is_flashing());
+    assert!(always.is_flashing());
+}
+
+#[test]
+fn triggered_flash_ends_by_itself() {
+    let mut lightning = Lightning::new(Some(3));
+    lightning.trigger(0.0);
+
+    assert!(lightning.update(30.0, 0.0) > 0.0);
+    assert_eq!(lightning.update(5000.0, 0.0), 0.0);
+    assert!(!lightning.is_flashing());
+}
@@ -0,0 +1,46 @@
+use rain_effect::noise::Noise;
+
+// 固定步长铺满几个格子的采样点
+fn samples() -> impl Iterator<Item = (f64, f64, f64)> {
+    (0..4000).map(|i| {
+        let i = i as f64;
+        (i * 0.137 - 200.0, i * 0.071, (i * 0.053).sin() * 30.0)
+    })
+}
+
+#[test]
+fn same_seed_gives_the_same_noise() {
+    let (a, b, c) = (Noise::new(42), Noise::new(42), Noise::new(7));
+    let mut differs = false;
+    for (x, y, z) in samples().take(200) {
+        assert_eq!(a.noise(x, y, z), b.noise(x, y, z));
+        assert_eq!(a.fbm(x, y, z, 3), b.fbm(x, y, z, 3));
+        differs |= a.noise(x, y, z) != c.noise(x, y, z);
+    }
+    assert!(differs);
+}
+
+#[test]
+fn noise_stays_in_range() {
+    let noise = Noise::new(1);
+    let mut max: f64 = 0.0;
+    for (x, y, z) in samples() {
+        max = max.max(noise.noise(x, y, z).abs());
+        assert!(noise.fbm(x, y, z, 4).abs() <= 1.0);
+    }
+    assert!(max <= 1.0 && max > 0.3);
+    // 整数格点上为 0
+    assert_eq!(noise.noise(3.0, -5.0, 12.0), 0.0);
+}
+
+#[test]
+fn nearby_samples_are_close() {
+    let noise = Noise::new(9);
+    let step = 0.001;
+    for (x, y, z) in samples() {
+        let n = noise.fbm(x, y, z, 3);
+        assert!((noise.fbm(x + step, y, z, 3) - n).abs() < 0.02);
+        assert!((noise.fbm(x, y + step, z, 3) - n).abs() < 0.02);
+        assert!((noise.fbm(x, y, z + step, 3) - n).abs() < 0.02);
+    }
+}
@@ -0,0 +1,29 @@
+use rain_effect::palette::Palette;
+use rain_effect::rgba::RgbaImage;
+
+#[test]
+fn median_cut_keeps_distinct_colors() {
+    let mut image = RgbaImage::filled(4, 4, [255, 0, 0, 255]);
+    image.set_pixel(0, 0, [0, 0, 255, 255]);
+    image.set_pixel(1, 0, [0, 255, 0, 255]);
+
+    let mut palette = Palette::median_cut(&[image.clone()], 256);
+    let indices = palette.index(&image, false);
+
+    assert_eq!(palette.colors().len(), 3);
+    for (i, p) in image.data.chunks(4).enumerate() {
+        assert_eq!(palette.colors()[indices[i] as usize], [p[0], p[1], p[2]]);
+    }
+}
+
+#[test]
+fn dithering_preserves_average_color() {
+    let image = RgbaImage::filled(32, 32, [128, 128, 128, 255]);
+    let mut palette = Palette::new(vec![[0, 0, 0], [255, 255, 255]]);
+
+    let indices = palette.index(&image, true);
+    let white = indices.iter().filter(|&&i| i == 1).count() as f64;
+
+    assert!((white / indices.len() as f64 - 0.5).abs() < 0.05);
+    assert!(palette.index(&image, false).iter().all(|&i| i == 1));
+}
@@ -0,0 +1,48 @@
+use rain_effect::parallax::{
+    orientation_input, pointer_input, scroll_input, Parallax, ParallaxSource,
+};
+
+#[test]
+fn inputs_are_centered_and_clamped() {
+    assert_eq!(pointer_input(400.0, 300.0, 800.0, 600.0), (0.0, 0.0));
+    assert_eq!(pointer_input(0.0, 900.0, 800.0, 600.0), (-1.0, 1.0));
+    assert_eq!(orientation_input(45.0, 0.0), (0.0, 0.0));
+    assert_eq!(orientation_input(-90.0, 60.0), (1.0, -1.0));
+    assert_eq!(scroll_input((0.0, 500.0), (0.0, 1000.0)), (0.0, 0.0));
+    assert_eq!(scroll_input((0.0, 1000.0), (0.0, 1000.0)).1, 1.0);
+}
+
+#[test]
+fn sources_are_weighted_by_gain() {
+    let mut parallax = Parallax::new();
+    parallax.set_smoothing(0.0);
+    parallax.set_input(ParallaxSource::Pointer, (0.5, 0.5));
+    assert_eq!(parallax.update(0.0), (0.0, 0.0));
+
+    parallax.set_gain(ParallaxSource::Pointer, 1.0);
+    parallax.set_gain(ParallaxSource::Scroll, 0.5);
+    parallax.set_input(ParallaxSource::Pointer, (0.5, -0.5));
+    parallax.set_input(ParallaxSource::Scroll, (0.0, 1.0));
+    assert_eq!(parallax.update(16.0), (0.5, 0.0));
+    assert_eq!(
+        parallax.sources(),
+        vec![ParallaxSource::Pointer, ParallaxSource::Scroll]
+    );
+
+    parallax.set_gain(ParallaxSource::Pointer, 4.0);
+    assert_eq!(parallax.update(32.0), (1.0, -1.0));
+}
+
+#[test]
+fn smoothing_eases_towards_the_target() {
+    let mut parallax = Parallax::new();
+    parallax.set_smoothing(100.0);
+    parallax.set_gain(ParallaxSource::Pointer, 1.0);
+    parallax.update(0.0);
+    parallax.set_input(ParallaxSource::Pointer, (1.0, 0.0));
+
+    let (x, _) = parallax.update(100.0);
+    assert!((x - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+    let (x, _) = parallax.update(1100.0);
+    assert!(x > 0.9999 && x <= 1.0);
+}
@@ -0,0 +1,64 @@
+use rain_effect::safety::{FlashLimiter, SafetyOptions};
+
+// 按 WCAG 的定义数出输出中幅度不小于 threshold 的相反方向变化
+fn transitions(values: &[(f64, f64)], threshold: f64) -> Vec<f64> {
+    let mut times = Vec::new();
+    let (mut extreme, mut rising) = (values[0].1, None);
+    for &(t, v) in values {
+        let up = v > extreme;
+        if (v - extreme).abs() >= threshold && rising != Some(up) {
+            times.push(t);
+            rising = Some(up);
+            extreme = v;
+        } else if rising == Some(up) {
+            extreme = v;
+        }
+    }
+    times
+}
+
+#[test]
+fn limits_flashes_per_second() {
+    let opts = SafetyOptions {
+        max_change: 1000.0,
+        ..SafetyOptions::new()
+    };
+    let mut limiter = FlashLimiter::new(opts);
+    // 20 Hz 的明暗交替
+    let values: Vec<(f64, f64)> = (0..300)
+        .map(|i| {
+            let t = i as f64 * 10.0;
+            let wanted = if (i / 5) % 2 == 0 { 1.0 } else { 0.0 };
+            (t, limiter.limit(t, wanted))
+        })
+        .collect();
+
+    let times = transitions(&values, opts.flash_threshold);
+    assert!(times.len() >= 6);
+    for (i, &t) in times.iter().enumerate() {
+        let in_window = times[i..].iter().take_while(|&&u| u - t < 1000.0).count();
+        assert!(in_window <= 6, "{} transitions within a second", in_window);
+    }
+    assert!(values.iter().all(|(_, v)| (0.0..=1.0).contains(v)));
+}
+
+#[test]
+fn limits_the_rate_of_change() {
+    let mut limiter = FlashLimiter::new(SafetyOptions::new());
+    limiter.limit(0.0, 0.0);
+
+    let v = limiter.limit(100.0, 1.0);
+    assert!((v - 0.4).abs() < 1e-9);
+    assert_eq!(limiter.limit(1000.0, 1.0), 1.0);
+    assert!((limiter.limit(1050.0, 0.0) - 0.8).abs() < 1e-9);
+}
+
+#[test]
+fn slow_changes_pass_through() {
+    let mut limiter = FlashLimiter::new(SafetyOptions::new());
+    for i in 0..=100 {
+        let t = i as f64 * 16.0;
+        let wanted = i as f64 / 100.0;
+        assert!((limiter.limit(t, wanted) - wanted).abs() < 1e-9);
+    }
+}
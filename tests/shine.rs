@@ -0,0 +1,41 @@
+use rain_effect::shine::{parse_color, shine_texture, Light};
+
+#[test]
+fn highlight_sits_towards_the_light() {
+    let light = Light {
+        direction: 90.0,
+        color: [255, 200, 100],
+        ..Light::new()
+    };
+    let shine = shine_texture(64, &light);
+
+    let top = shine.pixel(32, 18);
+    let bottom = shine.pixel(32, 46);
+    assert_eq!(&top[..3], &[255, 200, 100]);
+    assert!(top[3] > 150);
+    assert_eq!(bottom[3], 0);
+    assert_eq!(shine.pixel(18, 18)[3], shine.pixel(45, 18)[3]);
+}
+
+#[test]
+fn intensity_scales_the_highlight() {
+    let bright = shine_texture(32, &Light::new());
+    let off = shine_texture(
+        32,
+        &Light {
+            intensity: 0.0,
+            ..Light::new()
+        },
+    );
+
+    assert!(bright.data.chunks(4).any(|p| p[3] > 0));
+    assert!(off.data.chunks(4).all(|p| p[3] == 0));
+}
+
+#[test]
+fn parses_hex_colors() {
+    assert_eq!(parse_color("#ffcc00"), Some([255, 204, 0]));
+    assert_eq!(parse_color("0a0B0c"), Some([10, 11, 12]));
+    assert_eq!(parse_color("#fff"), None);
+    assert_eq!(parse_color("#gg0000"), None);
+}
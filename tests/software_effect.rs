@@ -0,0 +1,56 @@
+use rain_effect::rgba::RgbaImage;
+use rain_effect::software_effect::{SoftwareEffect, SoftwareEffectOptions, SoftwareImages};
+
+fn images() -> SoftwareImages {
+    SoftwareImages {
+        fg: RgbaImage::filled(32, 32, [200, 100, 50, 255]),
+        bg: RgbaImage::filled(32, 32, [0, 0, 255, 255]),
+        drop_alpha: RgbaImage::filled(16, 16, [0, 0, 0, 255]),
+        drop_color: RgbaImage::filled(16, 16, [128, 128, 0, 255]),
+    }
+}
+
+fn render(weather: &str, seed: u64) -> RgbaImage {
+    let opts = SoftwareEffectOptions {
+        width: 160,
+        height: 90,
+        dpi: 1.0,
+        seed: Some(seed),
+    };
+    let mut effect = SoftwareEffect::new(weather, &images(), Some(opts));
+    effect.warm_up(2.0);
+    effect.step(1.0);
+    effect.render()
+}
+
+#[test]
+fn same_seed_renders_same_frame() {
+    assert_eq!(render("rain", 7), render("rain", 7));
+    assert_ne!(render("rain", 7), render("rain", 8));
+}
+
+#[test]
+fn sun_renders_dry_glass() {
+    let image = render("sun", 7);
+
+    assert!(image.data.chunks(4).all(|p| p == [0, 0, 255, 255]));
+}
+
+#[test]
+fn resize_keeps_the_glass_wet() {
+    let opts = SoftwareEffectOptions {
+        width: 160,
+        height: 90,
+        dpi: 1.0,
+        seed: Some(7),
+    };
+    let mut effect = SoftwareEffect::new("rain", &images(), Some(opts));
+    effect.warm_up(2.0);
+
+    effect.resize(320, 180, 2.0);
+    effect.step(1.0);
+    let image = effect.render();
+
+    assert_eq!((image.width, image.height), (320, 180));
+    assert!(image.data.chunks(4).any(|p| p != [0, 0, 255, 255]));
+}
@@ -0,0 +1,159 @@
+use rain_effect::rgba::RgbaImage;
+use rain_effect::software_render::{SoftwareRender, SoftwareTextures};
+use rain_effect::RainRenderOptions;
+
+fn textures<'a>(
+    water_map: &'a RgbaImage,
+    fg: &'a RgbaImage,
+    bg: &'a RgbaImage,
+) -> SoftwareTextures<'a> {
+    SoftwareTextures {
+        water_map,
+        fg,
+        bg,
+        shine: None,
+        water_map_back: None,
+        next: None,
+    }
+}
+
+#[test]
+fn dry_glass_shows_background() {
+    let water_map = RgbaImage::new(64, 32);
+    let fg = RgbaImage::filled(32, 16, [255, 0, 0, 255]);
+    let bg = RgbaImage::filled(32, 16, [0, 0, 255, 255]);
+
+    let image = SoftwareRender::new(None).render(&textures(&water_map, &fg, &bg), 64, 32);
+
+    assert!(image.data.chunks(4).all(|p| p == [0, 0, 255, 255]));
+}
+
+#[test]
+fn drops_show_foreground_with_brightness() {
+    let water_map = RgbaImage::filled(64, 32, [128, 128, 255, 255]);
+    let fg = RgbaImage::filled(32, 16, [200, 100, 50, 255]);
+    let bg = RgbaImage::filled(32, 16, [0, 0, 255, 255]);
+    let opts = RainRenderOptions {
+        brightness: 0.5,
+        ..RainRenderOptions::default()
+    };
+
+    let image = SoftwareRender::new(Some(opts)).render(&textures(&water_map, &fg, &bg), 64, 32);
+
+    assert!(image.data.chunks(4).all(|p| p == [100, 50, 25, 255]));
+}
+
+#[test]
+fn cross_fade_mixes_next_background() {
+    let water_map = RgbaImage::new(16, 16);
+    let fg = RgbaImage::filled(16, 16, [0, 0, 0, 255]);
+    let bg = RgbaImage::filled(16, 16, [0, 0, 0, 255]);
+    let next_bg = RgbaImage::filled(16, 16, [200, 200, 200, 255]);
+    let mut textures = textures(&water_map, &fg, &bg);
+    textures.next = Some((&fg, &next_bg));
+
+    let mut render = SoftwareRender::new(None);
+    render.set_cross_fade(0.25);
+    let image = render.render(&textures, 16, 16);
+
+    assert_eq!(image.pixel(8, 8), [50, 50, 50, 255]);
+}
+
+#[test]
+fn transparent_water_map_pixels_do_not_bleed_into_drop_edges() {
+    // 透明像素的颜色不影响雨滴边缘的折射
+    let drop = [128, 128, 255, 255];
+    let mut water_map = RgbaImage::new(2, 1);
+    water_map.set_pixel(0, 0, drop);
+    let mut tinted = water_map.clone();
+    tinted.set_pixel(1, 0, [0, 255, 255, 0]);
+    let mut fg = RgbaImage::new(64, 32);
+    for x in 0..64 {
+        for y in 0..32 {
+            fg.set_pixel(x, y, [(x * 4) as u8, 0, 0, 255]);
+        }
+    }
+    let bg = RgbaImage::filled(32, 16, [0, 0, 255, 255]);
+    let opts = RainRenderOptions {
+        min_refraction: 16.0,
+        max_refraction: 16.0,
+        parallax_bg: 0.0,
+        parallax_fg: 0.0,
+        ..RainRenderOptions::default()
+    };
+    let render = SoftwareRender::new(Some(opts));
+
+    let clear = render.render(&textures(&water_map, &fg, &bg), 64, 32);
+    let edge = render.render(&textures(&tinted, &fg, &bg), 64, 32);
+
+    assert_eq!(clear.data, edge.data);
+    // 雨滴边缘没有折射偏移
+    assert_eq!(edge.pixel(30, 16), [120, 0, 0, 255]);
+}
+
+#[test]
+fn shine_is_filtered_with_premultiplied_alpha() {
+    let water_map = RgbaImage::filled(16, 16, [128, 128, 0, 255]);
+    let fg = RgbaImage::filled(16, 16, [0, 0, 0, 255]);
+    let bg = RgbaImage::filled(16, 16, [0, 0, 0, 255]);
+    // 不透明的红和全透明的蓝，中间采样时透明的一侧不带入颜色
+    let shine = RgbaImage::from_raw(2, 1, vec![255, 0, 0, 255, 0, 0, 255, 0]).unwrap();
+    let mut textures = textures(&water_map, &fg, &bg);
+    textures.shine = Some(&shine);
+
+    let image = SoftwareRender::new(None).render(&textures, 16, 16);
+
+    let [r, g, b, _] = image.pixel(8, 8);
+    assert!((126..=130).contains(&r), "{}", r);
+    assert_eq!((g, b), (0, 0));
+}
+
+#[test]
+fn flash_brightens_drops_and_shine() {
+    let water_map = RgbaImage::filled(16, 16, [128, 128, 0, 255]);
+    let fg = RgbaImage::filled(16, 16, [40, 40, 40, 255]);
+    let bg = RgbaImage::filled(16, 16, [0, 0, 255, 255]);
+    let shine = RgbaImage::filled(4, 4, [255, 255, 255, 51]);
+    let mut textures = textures(&water_map, &fg, &bg);
+
+    let mut render = SoftwareRender::new(None);
+    render.set_flash(1.0);
+    assert_eq!(
+        render.render(&textures, 16, 16).pixel(8, 8),
+        [60, 60, 60, 255]
+    );
+
+    // 高光的透明度提高到 3 倍：0.2 -> 0.6
+    textures.shine = Some(&shine);
+    render.set_flash(0.0);
+    assert_eq!(
+        render.render(&textures, 16, 16).pixel(8, 8),
+        [83, 83, 83, 255]
+    );
+    render.set_flash(1.0);
+    assert_eq!(
+        render.render(&textures, 16, 16).pixel(8, 8),
+        [254, 254, 254, 255]
+    );
+}
+
+#[test]
+fn red_saturation_is_limited() {
+    let water_map = RgbaImage::new(16, 16);
+    let fg = RgbaImage::filled(16, 16, [0, 0, 0, 255]);
+    let bg = RgbaImage::filled(16, 16, [240, 30, 30, 255]);
+    let red = textures(&water_map, &fg, &bg);
+
+    let mut render = SoftwareRender::new(None);
+    assert_eq!(render.render(&red, 16, 16).pixel(8, 8), [240, 30, 30, 255]);
+
+    render.set_max_red_saturation(Some(0.5));
+    let [r, g, b, _] = render.render(&red, 16, 16).pixel(8, 8);
+    // R / (R + G + B) 降到 0.5，亮度之和不变
+    assert_eq!((r, g, b), (150, 75, 75));
+
+    // 不饱和的颜色不变
+    let grey = RgbaImage::filled(16, 16, [120, 100, 100, 255]);
+    let image = render.render(&textures(&water_map, &fg, &grey), 16, 16);
+    assert_eq!(image.pixel(8, 8), [120, 100, 100, 255]);
+}
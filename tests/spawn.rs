@@ -0,0 +1,90 @@
+use rain_effect::spawn::{
+    ClusteredSpawn, DensitySpawn, EdgeSpawn, SpawnArea, SpawnStrategy, UniformSpawn,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn area() -> SpawnArea {
+    SpawnArea {
+        width: 800.0,
+        height: 600.0,
+        y_range: (-100.0, 300.0),
+    }
+}
+
+#[test]
+fn spawns_stay_inside_the_area() {
+    let area = area();
+    let mut rng = StdRng::seed_from_u64(3);
+    let strategies: Vec<Box<dyn SpawnStrategy>> = vec![
+        Box::new(UniformSpawn),
+        Box::new(ClusteredSpawn::new()),
+        Box::new(EdgeSpawn::new()),
+    ];
+
+    for mut strategy in strategies {
+        for _ in 0..2000 {
+            let spawn = strategy.spawn(&area, &mut rng);
+            assert!((0.0..=area.width).contains(&spawn.x));
+            assert!((area.y_range.0..=area.y_range.1).contains(&spawn.y));
+            assert!((0.0..=1.0).contains(&spawn.size));
+        }
+    }
+
+    let mut edge = EdgeSpawn { depth: 0.1 };
+    for _ in 0..500 {
+        assert!(edge.spawn(&area, &mut rng).y <= 60.0);
+    }
+}
+
+#[test]
+fn clusters_group_the_drops() {
+    let area = area();
+    let mut rng = StdRng::seed_from_u64(5);
+    let mut clustered = ClusteredSpawn::new();
+    clustered.clusters = 1;
+    clustered.drift = 0.0;
+
+    let spawns: Vec<_> = (0..500).map(|_| clustered.spawn(&area, &mut rng)).collect();
+    let mean = spawns.iter().map(|s| s.x).sum::<f64>() / spawns.len() as f64;
+    let near = spawns.iter().filter(|s| (s.x - mean).abs() < 150.0).count();
+    assert!(near > 450);
+}
+
+#[test]
+fn density_spawn_follows_the_weight_image() {
+    let area = area();
+    let mut rng = StdRng::seed_from_u64(11);
+    // 4 x 1：黑、白、透明的白、灰（亮度一半）
+    let rgba = [
+        0, 0, 0, 255, //
+        255, 255, 255, 255, //
+        255, 255, 255, 0, //
+        128, 128, 128, 255,
+    ];
+    let mut density = DensitySpawn::new(4, 1, &rgba);
+
+    let mut counts = [0; 4];
+    for _ in 0..3000 {
+        let spawn = density.spawn(&area, &mut rng);
+        assert!((0.0..area.width).contains(&spawn.x));
+        assert!((0.0..area.height).contains(&spawn.y));
+        counts[(spawn.x / area.width * 4.0) as usize] += 1;
+    }
+    assert_eq!(counts[0], 0);
+    assert_eq!(counts[2], 0);
+    // 白和灰约为 2 : 1
+    let ratio = counts[1] as f64 / counts[3] as f64;
+    assert!((1.7..2.3).contains(&ratio), "{:?}", counts);
+}
+
+#[test]
+fn empty_density_falls_back_to_uniform() {
+    let area = area();
+    let mut rng = StdRng::seed_from_u64(13);
+    let mut density = DensitySpawn::new(2, 2, &[0; 16]);
+    for _ in 0..200 {
+        let spawn = density.spawn(&area, &mut rng);
+        assert!((area.y_range.0..=area.y_range.1).contains(&spawn.y));
+    }
+}
@@ -0,0 +1,23 @@
+use rain_effect::rgba::RgbaImage;
+use rain_effect::terminal::{ansi, ascii};
+
+#[test]
+fn ansi_draws_two_pixels_per_character() {
+    let mut image = RgbaImage::filled(2, 3, [10, 20, 30, 255]);
+    image.set_pixel(1, 1, [40, 50, 60, 255]);
+
+    assert_eq!(
+        ansi(&image),
+        "\x1b[38;2;10;20;30m\x1b[48;2;10;20;30m▀\x1b[38;2;10;20;30m\x1b[48;2;40;50;60m▀\x1b[0m\n\
+         \x1b[38;2;10;20;30m\x1b[49m▀▀\x1b[0m\n"
+    );
+}
+
+#[test]
+fn ascii_shows_drops_by_depth() {
+    let mut water_map = RgbaImage::new(4, 2);
+    water_map.set_pixel(1, 0, [128, 128, 0, 255]);
+    water_map.set_pixel(2, 1, [128, 128, 255, 255]);
+
+    assert_eq!(ascii(&water_map, 4, 2, 6.0, 3.0), " .\n  @\n");
+}
@@ -0,0 +1,105 @@
+use rain_effect::drop::Drop;
+use rain_effect::trail::{Trail, TrailPoint};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// 从 (0, 0) 垂直向下每 10 像素一个点
+fn straight_trail(drop: &std::rc::Rc<std::cell::RefCell<Drop>>, points: usize) -> Trail {
+    let mut trail = Trail::new(drop);
+    for i in 0..points {
+        trail.push(0.0, i as f64 * 10.0, 2.0);
+    }
+    trail
+}
+
+#[test]
+fn push_merges_close_points() {
+    let drop = Drop::new();
+    let mut trail = Trail::new(&drop);
+    trail.push(0.0, 0.0, 2.0);
+    trail.push(0.0, 1.0, 3.0);
+    assert_eq!(trail.points.len(), 1);
+    assert_eq!(trail.points[0].r, 3.0);
+
+    trail.push(0.0, 10.0, 2.0);
+    assert_eq!(trail.points.len(), 2);
+    assert!(trail.owner().is_some());
+}
+
+#[test]
+fn narrowing_thins_with_age() {
+    let point = |age| TrailPoint {
+        x: 0.0,
+        y: 0.0,
+        r: 2.0,
+        age,
+    };
+    assert_eq!(Trail::narrowing(&point(0.0), 100.0), 1.0);
+    assert!((Trail::narrowing(&point(50.0), 100.0) - 0.7).abs() < 1e-9);
+    assert!((Trail::narrowing(&point(300.0), 100.0) - 0.4).abs() < 1e-9);
+}
+
+#[test]
+fn beads_are_spaced_along_the_trail() {
+    let drop = Drop::new();
+    let mut rng = StdRng::seed_from_u64(2);
+    let mut trail = straight_trail(&drop, 11);
+    trail.update(60.0);
+
+    let beads = trail.take_beads(50.0, &mut rng);
+    assert!(beads.len() >= 5);
+    for (x, y, r) in &beads {
+        assert_eq!(*x, 0.0);
+        assert!((0.0..=100.0).contains(y));
+        assert!((1.6..=2.8).contains(r));
+    }
+    // 相邻水珠的间距为前一颗半径的 3 ~ 7 倍
+    for pair in beads.windows(2) {
+        let spacing = (pair[1].1 - pair[0].1) / pair[0].2;
+        assert!((3.0..=7.0).contains(&spacing), "{}", spacing);
+    }
+    assert!(trail.points.is_empty());
+    assert!(trail.is_finished(50.0));
+}
+
+#[test]
+fn zero_radius_trail_still_terminates() {
+    let drop = Drop::new();
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut trail = Trail::new(&drop);
+    for i in 0..5 {
+        trail.push(0.0, i as f64 * 10.0, 0.0);
+    }
+    trail.update(60.0);
+
+    let beads = trail.take_beads(50.0, &mut rng);
+    assert!(!beads.is_empty());
+    assert!(beads.iter().all(|&(_, _, r)| r == 0.0));
+    assert!(trail.is_finished(50.0));
+}
+
+#[test]
+fn only_old_points_bead_up() {
+    let drop = Drop::new();
+    let mut rng = StdRng::seed_from_u64(4);
+    let mut trail = straight_trail(&drop, 6);
+    trail.update(60.0);
+    for i in 6..11 {
+        trail.push(0.0, i as f64 * 10.0, 2.0);
+    }
+
+    let beads = trail.take_beads(50.0, &mut rng);
+    assert!(beads.iter().all(|&(_, y, _)| y <= 60.0));
+    assert_eq!(trail.points.len(), 5);
+    assert!(!trail.is_finished(50.0));
+
+    // 还在延长的水痕保留最后一个点
+    let mut trail = straight_trail(&drop, 4);
+    trail.update(60.0);
+    trail.push(0.0, 30.0, 2.0);
+    trail.take_beads(50.0, &mut rng);
+    assert_eq!(trail.points.len(), 1);
+    assert!(!trail.is_finished(50.0));
+    trail.update(60.0);
+    assert!(trail.is_finished(50.0));
+}
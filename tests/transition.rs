@@ -0,0 +1,61 @@
+use rain_effect::transition::Transition;
+use rain_effect::weather::{Weather, WeatherOptions};
+
+fn transition(duration: f64) -> Transition<&'static str> {
+    let sun = WeatherOptions {
+        raining: false,
+        rain_chance: 0.0,
+        ..WeatherOptions::new()
+    };
+    Transition::new(
+        WeatherOptions::new(),
+        Weather::new("sun", sun),
+        "sun",
+        1000.0,
+        duration,
+    )
+}
+
+#[test]
+fn progress_eases_from_start_to_end() {
+    let transition = transition(2000.0);
+
+    assert_eq!(transition.progress(0.0), 0.0);
+    assert_eq!(transition.progress(1000.0), 0.0);
+    assert_eq!(transition.progress(2000.0), 0.5);
+    assert!(transition.progress(1200.0) < 0.1);
+    assert_eq!(transition.progress(3000.0), 1.0);
+    assert_eq!(transition.progress(5000.0), 1.0);
+
+    assert_eq!(transition.options(1000.0).rain_chance, 0.35);
+    assert!(transition.options(2000.0).raining);
+    assert_eq!(transition.options(3000.0).rain_chance, 0.0);
+}
+
+#[test]
+fn finish_returns_the_target() {
+    let transition = transition(2000.0);
+    assert!(!transition.is_finished(2999.0));
+    assert!(transition.is_finished(3000.0));
+
+    let (weather, image) = transition.finish();
+    assert_eq!(weather.to_string(), "sun");
+    assert!(!weather.options().raining);
+    assert_eq!(image, "sun");
+}
+
+#[test]
+fn delay_pushes_back_the_end() {
+    let mut transition = transition(2000.0);
+    transition.delay(500.0);
+    assert_eq!(transition.progress(2500.0), 0.5);
+    assert!(!transition.is_finished(3000.0));
+    assert!(transition.is_finished(3500.0));
+}
+
+#[test]
+fn zero_duration_finishes_immediately() {
+    let transition = transition(0.0);
+    assert!(transition.is_finished(1000.0));
+    assert_eq!(transition.progress(1000.0), 1.0);
+}
@@ -0,0 +1,43 @@
+use rain_effect::weather::WeatherOptions;
+
+fn sun() -> WeatherOptions {
+    WeatherOptions {
+        raining: false,
+        r: (20.0, 30.0),
+        rain_chance: 0.0,
+        rain_limit: 0.0,
+        droplets_rate: 0.0,
+        trail_scale_range: [0.25, 0.45],
+        flash_chance: 0.1,
+        ..WeatherOptions::new()
+    }
+}
+
+#[test]
+fn lerp_mixes_numeric_options() {
+    let rain = WeatherOptions::new();
+    let sun = sun();
+
+    let start = rain.lerp(&sun, 0.0);
+    assert_eq!(start.r, rain.r);
+    assert_eq!(start.rain_chance, rain.rain_chance);
+
+    let half = rain.lerp(&sun, 0.5);
+    assert_eq!(half.r, (20.0, 40.0));
+    assert_eq!(half.droplets_rate, 25.0);
+    assert_eq!(half.flash_chance, 0.05);
+
+    let end = rain.lerp(&sun, 2.0);
+    assert_eq!(end.trail_scale_range, sun.trail_scale_range);
+    assert_eq!(end.rain_limit, 0.0);
+}
+
+#[test]
+fn lerp_keeps_raining_until_the_end() {
+    let rain = WeatherOptions::new();
+    let sun = sun();
+
+    assert!(rain.lerp(&sun, 0.99).raining);
+    assert!(!rain.lerp(&sun, 1.0).raining);
+    assert!(sun.lerp(&rain, 0.01).raining);
+}